@@ -0,0 +1,31 @@
+// Detects the features of the compiler which are newer than the MSRV.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let output = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .expect("failed to run rustc --version");
+    let version = String::from_utf8(output.stdout).expect("rustc version is not UTF-8");
+    let minor = version
+        .split('.')
+        .nth(1)
+        .and_then(|minor| minor.parse::<u32>().ok())
+        .expect("unexpected rustc version format");
+
+    // Cargo reruns the script when the compiler changes, which is all it depends on.
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(rust_v_1_46)");
+    println!("cargo:rustc-check-cfg=cfg(rust_v_1_61)");
+    // Loops and branches in const fns.
+    if minor >= 46 {
+        println!("cargo:rustc-cfg=rust_v_1_46");
+    }
+    // Panics and trait bounds in const fns.
+    if minor >= 61 {
+        println!("cargo:rustc-cfg=rust_v_1_61");
+    }
+}
@@ -0,0 +1,65 @@
+
+extern crate bitcoin_hashes;
+
+use bitcoin_hashes::sha256::fuzz::{compress_block, compress_block_portable};
+
+// Compares the compression function used by the engine, which may be hardware-accelerated, with
+// the portable one. The data is an initial state followed by the blocks to compress.
+fn do_test(data: &[u8]) {
+    if data.len() < 32 {
+        return;
+    }
+
+    let mut state = [0u32; 8];
+    for (word, bytes) in state.iter_mut().zip(data.chunks(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+    let mut portable_state = state;
+
+    for chunk in data[32..].chunks(64) {
+        // The last block is padded with zeros
+        let mut block = [0u8; 64];
+        block[..chunk.len()].copy_from_slice(chunk);
+        compress_block(&mut state, &block);
+        compress_block_portable(&mut portable_state, &block);
+        assert_eq!(state, portable_state);
+    }
+}
+
+#[cfg(feature = "honggfuzz")]
+#[macro_use]
+extern crate honggfuzz;
+
+#[cfg(feature = "honggfuzz")]
+fn main() {
+    loop {
+        fuzz!(|d| { do_test(d) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn extend_vec_from_hex(hex: &str, out: &mut Vec<u8>) {
+        let mut b = 0;
+        for (idx, c) in hex.as_bytes().iter().enumerate() {
+            b <<= 4;
+            match *c {
+                b'A'...b'F' => b |= c - b'A' + 10,
+                b'a'...b'f' => b |= c - b'a' + 10,
+                b'0'...b'9' => b |= c - b'0',
+                _ => panic!("Bad hex"),
+            }
+            if (idx & 1) == 1 {
+                out.push(b);
+                b = 0;
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_crash() {
+        let mut a = Vec::new();
+        extend_vec_from_hex("6a09e667bb67ae853c6ef372a54ff53a510e527f9b05688c1f83d9ab5be0cd1900", &mut a);
+        super::do_test(&a);
+    }
+}
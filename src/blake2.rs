@@ -0,0 +1,707 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! BLAKE2 (RFC 7693) implementation.
+//!
+//! [`Blake2b`] and [`Blake2s`] are the general engines, supporting any digest length up to
+//! 64 and 32 bytes respectively, as well as the keyed (MAC), salted and personalized modes.
+//! The common fixed-length variants are available as hash types in the [`blake2b_256`],
+//! [`blake2b_512`] and [`blake2s_256`] submodules.
+//!
+
+use core::cmp;
+
+/// Message word permutations, one per round (rounds past the tenth wrap around).
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+const BLAKE2S_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+    0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+macro_rules! blake2_engine {
+    (
+        $(#[$doc:meta])*
+        $engine:ident, $word:ty, $counter:ty, $iv:expr, $rounds:expr,
+        ($r1:expr, $r2:expr, $r3:expr, $r4:expr),
+        block_size: $block_size:expr, max_out: $max_out:expr, salt: $salt_len:expr
+    ) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $engine {
+            h: [$word; 8],
+            counter: $counter,
+            buffer: [u8; $block_size],
+            buf_len: usize,
+            out_len: usize,
+            length: usize,
+        }
+
+        impl $engine {
+            /// Creates an unkeyed engine producing a digest of `out_len` bytes.
+            ///
+            /// # Panics
+            ///
+            /// If `out_len` is zero or greater than the maximum digest length.
+            pub fn new(out_len: usize) -> $engine {
+                $engine::with_params(out_len, &[], &[0; $salt_len], &[0; $salt_len])
+            }
+
+            /// Creates an engine producing a digest of `out_len` bytes, keyed with `key` and
+            /// parameterized with the given salt and personalization string.
+            ///
+            /// An empty key gives the unkeyed hash function, and all-zero salt and
+            /// personalization are equivalent to not using them.
+            ///
+            /// # Panics
+            ///
+            /// If `out_len` is zero or greater than the maximum digest length, or if `key` is
+            /// longer than the maximum digest length.
+            pub fn with_params(
+                out_len: usize,
+                key: &[u8],
+                salt: &[u8; $salt_len],
+                personal: &[u8; $salt_len],
+            ) -> $engine {
+                assert!(out_len > 0 && out_len <= $max_out, "invalid BLAKE2 output length {}", out_len);
+                assert!(key.len() <= $max_out, "invalid BLAKE2 key length {}", key.len());
+
+                const WORD_SIZE: usize = $block_size / 16;
+                let mut h = $iv;
+                h[0] ^= 0x01010000 ^ ((key.len() as $word) << 8) ^ out_len as $word;
+                for (i, chunk) in salt.chunks_exact(WORD_SIZE).chain(personal.chunks_exact(WORD_SIZE)).enumerate() {
+                    let mut word = [0; WORD_SIZE];
+                    word.copy_from_slice(chunk);
+                    h[4 + i] ^= <$word>::from_le_bytes(word);
+                }
+
+                let mut ret = $engine {
+                    h,
+                    counter: 0,
+                    buffer: [0; $block_size],
+                    buf_len: 0,
+                    out_len,
+                    length: 0,
+                };
+                // A key is processed as a full, zero-padded, first block.
+                if !key.is_empty() {
+                    ret.buffer[..key.len()].copy_from_slice(key);
+                    ret.buf_len = $block_size;
+                }
+                ret
+            }
+
+            /// Length of the digest produced by this engine, in bytes.
+            pub fn out_len(&self) -> usize {
+                self.out_len
+            }
+
+            /// Finalizes the engine, writing the digest to `out`.
+            ///
+            /// # Panics
+            ///
+            /// If `out.len()` differs from the output length the engine was created with.
+            pub fn finalize_into(mut self, out: &mut [u8]) {
+                assert_eq!(out.len(), self.out_len, "BLAKE2 output buffer has the wrong length");
+
+                self.counter += self.buf_len as $counter;
+                for byte in self.buffer[self.buf_len..].iter_mut() {
+                    *byte = 0;
+                }
+                self.compress(true);
+
+                const WORD_SIZE: usize = $block_size / 16;
+                for (chunk, word) in out.chunks_mut(WORD_SIZE).zip(self.h.iter()) {
+                    chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+                }
+            }
+
+            fn compress(&mut self, last: bool) {
+                const WORD_SIZE: usize = $block_size / 16;
+                const WORD_BITS: u32 = 8 * WORD_SIZE as u32;
+
+                let mut m = [0 as $word; 16];
+                for (word, chunk) in m.iter_mut().zip(self.buffer.chunks_exact(WORD_SIZE)) {
+                    let mut bytes = [0; WORD_SIZE];
+                    bytes.copy_from_slice(chunk);
+                    *word = <$word>::from_le_bytes(bytes);
+                }
+
+                let mut v = [0 as $word; 16];
+                v[..8].copy_from_slice(&self.h);
+                v[8..].copy_from_slice(&$iv);
+                v[12] ^= self.counter as $word;
+                v[13] ^= (self.counter >> WORD_BITS) as $word;
+                if last {
+                    v[14] = !v[14];
+                }
+
+                macro_rules! g {
+                    ($a:expr, $b:expr, $c:expr, $d:expr, $x:expr, $y:expr) => {
+                        v[$a] = v[$a].wrapping_add(v[$b]).wrapping_add($x);
+                        v[$d] = (v[$d] ^ v[$a]).rotate_right($r1);
+                        v[$c] = v[$c].wrapping_add(v[$d]);
+                        v[$b] = (v[$b] ^ v[$c]).rotate_right($r2);
+                        v[$a] = v[$a].wrapping_add(v[$b]).wrapping_add($y);
+                        v[$d] = (v[$d] ^ v[$a]).rotate_right($r3);
+                        v[$c] = v[$c].wrapping_add(v[$d]);
+                        v[$b] = (v[$b] ^ v[$c]).rotate_right($r4);
+                    };
+                }
+
+                for round in 0..$rounds {
+                    let s = &SIGMA[round % 10];
+                    g!(0, 4, 8, 12, m[s[0]], m[s[1]]);
+                    g!(1, 5, 9, 13, m[s[2]], m[s[3]]);
+                    g!(2, 6, 10, 14, m[s[4]], m[s[5]]);
+                    g!(3, 7, 11, 15, m[s[6]], m[s[7]]);
+                    g!(0, 5, 10, 15, m[s[8]], m[s[9]]);
+                    g!(1, 6, 11, 12, m[s[10]], m[s[11]]);
+                    g!(2, 7, 8, 13, m[s[12]], m[s[13]]);
+                    g!(3, 4, 9, 14, m[s[14]], m[s[15]]);
+                }
+
+                for (i, word) in self.h.iter_mut().enumerate() {
+                    *word ^= v[i] ^ v[i + 8];
+                }
+            }
+        }
+
+        impl Default for $engine {
+            fn default() -> Self {
+                $engine::new($max_out)
+            }
+        }
+
+        impl crate::HashEngine for $engine {
+            type MidState = [u8; $max_out];
+
+            fn midstate(&self) -> [u8; $max_out] {
+                const WORD_SIZE: usize = $block_size / 16;
+                let mut ret = [0; $max_out];
+                for (val, ret_bytes) in self.h.iter().zip(ret.chunks_exact_mut(WORD_SIZE)) {
+                    ret_bytes.copy_from_slice(&val.to_le_bytes());
+                }
+                ret
+            }
+
+            const BLOCK_SIZE: usize = $block_size;
+
+            fn n_bytes_hashed(&self) -> usize {
+                self.length
+            }
+
+            fn input(&mut self, mut inp: &[u8]) {
+                self.length += inp.len();
+                while !inp.is_empty() {
+                    // The last block has to be compressed with the finalization flag set, so a
+                    // full buffer is only compressed once we know more data follows it.
+                    if self.buf_len == $block_size {
+                        self.counter += $block_size;
+                        self.compress(false);
+                        self.buf_len = 0;
+                    }
+                    let n = cmp::min($block_size - self.buf_len, inp.len());
+                    self.buffer[self.buf_len..self.buf_len + n].copy_from_slice(&inp[..n]);
+                    self.buf_len += n;
+                    inp = &inp[n..];
+                }
+            }
+        }
+    };
+}
+
+blake2_engine!(
+    /// Engine to compute BLAKE2b with a digest length of up to 64 bytes.
+    Blake2b, u64, u128, BLAKE2B_IV, 12, (32, 24, 16, 63),
+    block_size: 128, max_out: 64, salt: 16
+);
+
+blake2_engine!(
+    /// Engine to compute BLAKE2s with a digest length of up to 32 bytes.
+    Blake2s, u32, u64, BLAKE2S_IV, 10, (16, 12, 8, 7),
+    block_size: 64, max_out: 32, salt: 8
+);
+
+macro_rules! fixed_engine {
+    ($(#[$doc:meta])* $inner:ident, $out_len:expr, $salt_len:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct HashEngine($inner);
+
+        impl HashEngine {
+            /// Creates an engine keyed with `key` and parameterized with the given salt and
+            /// personalization string.
+            ///
+            /// # Panics
+            ///
+            /// If `key` is longer than the maximum key length of the hash function.
+            pub fn with_params(key: &[u8], salt: &[u8; $salt_len], personal: &[u8; $salt_len]) -> HashEngine {
+                HashEngine($inner::with_params($out_len, key, salt, personal))
+            }
+        }
+
+        impl Default for HashEngine {
+            fn default() -> Self {
+                HashEngine($inner::new($out_len))
+            }
+        }
+
+        impl crate::HashEngine for HashEngine {
+            type MidState = <$inner as crate::HashEngine>::MidState;
+
+            fn midstate(&self) -> Self::MidState {
+                self.0.midstate()
+            }
+
+            const BLOCK_SIZE: usize = <$inner as crate::HashEngine>::BLOCK_SIZE;
+
+            fn n_bytes_hashed(&self) -> usize {
+                self.0.n_bytes_hashed()
+            }
+
+            fn input(&mut self, inp: &[u8]) {
+                self.0.input(inp)
+            }
+        }
+
+        fn from_engine(e: HashEngine) -> Hash {
+            let mut ret = [0; $out_len];
+            e.0.finalize_into(&mut ret);
+            Hash(ret)
+        }
+    };
+}
+
+/// BLAKE2b hash function with a 256-bit digest.
+pub mod blake2b_256 {
+    use core::str;
+    use core::ops::Index;
+    use core::slice::SliceIndex;
+
+    use super::Blake2b;
+    use crate::{Error, hex};
+
+    crate::internal_macros::hash_type! {
+        256,
+        false,
+        "Output of the BLAKE2b-256 hash function.",
+        "crate::util::json_hex_string::len_32"
+    }
+
+    fixed_engine!(
+        /// Engine to compute the BLAKE2b-256 hash function.
+        Blake2b, 32, 16
+    );
+}
+
+/// BLAKE2b hash function with a 512-bit digest.
+pub mod blake2b_512 {
+    use core::{cmp, hash, str};
+    use core::ops::Index;
+    use core::slice::SliceIndex;
+
+    use super::Blake2b;
+    use crate::{Error, hex};
+
+    crate::internal_macros::hash_trait_impls!(512, false);
+
+    /// Output of the BLAKE2b-512 hash function.
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+    #[repr(transparent)]
+    pub struct Hash(
+        #[cfg_attr(feature = "schemars", schemars(schema_with = "crate::util::json_hex_string::len_64"))]
+        [u8; 64]
+    );
+
+    impl Hash {
+        const fn internal_new(arr: [u8; 64]) -> Self {
+            Hash(arr)
+        }
+
+        fn internal_engine() -> HashEngine {
+            Default::default()
+        }
+    }
+
+    impl Copy for Hash {}
+
+    impl Clone for Hash {
+        fn clone(&self) -> Hash {
+            *self
+        }
+    }
+
+    impl PartialEq for Hash {
+        fn eq(&self, other: &Hash) -> bool {
+            self.0[..] == other.0[..]
+        }
+    }
+
+    impl Eq for Hash {}
+
+    impl Default for Hash {
+        fn default() -> Hash {
+            Hash([0; 64])
+        }
+    }
+
+    impl PartialOrd for Hash {
+        fn partial_cmp(&self, other: &Hash) -> Option<cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Hash {
+        fn cmp(&self, other: &Hash) -> cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl hash::Hash for Hash {
+        fn hash<H: hash::Hasher>(&self, state: &mut H) {
+            self.0.hash(state)
+        }
+    }
+
+    fixed_engine!(
+        /// Engine to compute the BLAKE2b-512 hash function.
+        Blake2b, 64, 16
+    );
+}
+
+/// BLAKE2s hash function with a 256-bit digest.
+pub mod blake2s_256 {
+    use core::str;
+    use core::ops::Index;
+    use core::slice::SliceIndex;
+
+    use super::Blake2s;
+    use crate::{Error, hex};
+
+    crate::internal_macros::hash_type! {
+        256,
+        false,
+        "Output of the BLAKE2s-256 hash function.",
+        "crate::util::json_hex_string::len_32"
+    }
+
+    fixed_engine!(
+        /// Engine to compute the BLAKE2s-256 hash function.
+        Blake2s, 32, 8
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Hash, HashEngine};
+    use super::{blake2b_256, blake2b_512, blake2s_256, Blake2b, Blake2s};
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::hex::{FromHex, ToHex};
+
+        struct Test {
+            input: &'static str,
+            blake2b_256: &'static str,
+            blake2b_512: &'static str,
+            blake2s_256: &'static str,
+        }
+
+        let tests = vec![
+            // Test vectors from RFC 7693 and the reference implementation
+            Test {
+                input: "",
+                blake2b_256: "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8",
+                blake2b_512: "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+                              d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+                blake2s_256: "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+            },
+            Test {
+                input: "abc",
+                blake2b_256: "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319",
+                blake2b_512: "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                              7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+                blake2s_256: "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+            },
+            Test {
+                input: "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                blake2b_256: "5f7a93da9c5621583f22e49e8e91a40cbba37536622235a380f434b9f68e49c4",
+                blake2b_512: "7285ff3e8bd768d69be62b3bf18765a325917fa9744ac2f582a20850bc2b1141\
+                              ed1b3e4528595acc90772bdf2d37dc8a47130b44f33a02e8730e5ad8e166e888",
+                blake2s_256: "6f4df5116a6f332edab1d9e10ee87df6557beab6259d7663f3bcd5722c13f189",
+            },
+        ];
+
+        for test in tests {
+            let hash = blake2b_256::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, blake2b_256::Hash::from_hex(test.blake2b_256).expect("parse hex"));
+            assert_eq!(hash.to_hex(), test.blake2b_256);
+
+            let hash = blake2b_512::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, blake2b_512::Hash::from_hex(test.blake2b_512).expect("parse hex"));
+            assert_eq!(hash.to_hex(), test.blake2b_512);
+
+            let hash = blake2s_256::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, blake2s_256::Hash::from_hex(test.blake2s_256).expect("parse hex"));
+            assert_eq!(hash.to_hex(), test.blake2s_256);
+
+            // Hashing byte by byte must give the same result
+            let mut engine = blake2b_512::Hash::engine();
+            for ch in test.input.as_bytes() {
+                engine.input(&[*ch]);
+            }
+            assert_eq!(blake2b_512::Hash::from_engine(engine).to_hex(), test.blake2b_512);
+
+            let mut engine = blake2s_256::Hash::engine();
+            for ch in test.input.as_bytes() {
+                engine.input(&[*ch]);
+            }
+            assert_eq!(blake2s_256::Hash::from_engine(engine).to_hex(), test.blake2s_256);
+        }
+
+        // A million 'a's
+        let mut b_engine = blake2b_256::Hash::engine();
+        let mut s_engine = blake2s_256::Hash::engine();
+        for _ in 0..10000 {
+            b_engine.input(&[b'a'; 100]);
+            s_engine.input(&[b'a'; 100]);
+        }
+        assert_eq!(
+            blake2b_256::Hash::from_engine(b_engine).to_hex(),
+            "0741850f36cba4259628355d1073e24ddb9ca0e1bfac36fd39ae5dc2101e23a4",
+        );
+        assert_eq!(
+            blake2s_256::Hash::from_engine(s_engine).to_hex(),
+            "bec0c0e6cde5b67acb73b81f79a67a4079ae1c60dac9d2661af18e9f8b50dfa5",
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn keyed() {
+        use crate::hex::ToHex;
+
+        // Known-answer tests from the reference implementation: the key is 0x00..0x3f (0x00..0x1f
+        // for BLAKE2s) and the message is 0x00, 0x01, ... of the given length. The lengths cover
+        // the block boundaries.
+        let b_tests = [
+            (0, "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786\
+                 b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568"),
+            (1, "961f6dd1e4dd30f63901690c512e78e4b45e4742ed197c3c5e45c549fd25f2e4\
+                 187b0bc9fe30492b16b0d0bc4ef9b0f34c7003fac09a5ef1532e69430234cebd"),
+            (127, "76d2d819c92bce55fa8e092ab1bf9b9eab237a25267986cacf2b8ee14d214d73\
+                   0dc9a5aa2d7b596e86a1fd8fa0804c77402d2fcd45083688b218b1cdfa0dcbcb"),
+            (128, "72065ee4dd91c2d8509fa1fc28a37c7fc9fa7d5b3f8ad3d0d7a25626b57b1b44\
+                   788d4caf806290425f9890a3a2a35a905ab4b37acfd0da6e4517b2525c9651e4"),
+            (129, "64475dfe7600d7171bea0b394e27c9b00d8e74dd1e416a79473682ad3dfdbb70\
+                   6631558055cfc8a40e07bd015a4540dcdea15883cbbf31412df1de1cd4152b91"),
+            (255, "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e9248\
+                   4be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461"),
+        ];
+        let s_tests = [
+            (0, "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49"),
+            (1, "40d15fee7c328830166ac3f918650f807e7e01e177258cdc0a39b11f598066f1"),
+            (63, "c65382513f07460da39833cb666c5ed82e61b9e998f4b0c4287cee56c3cc9bcd"),
+            (64, "8975b0577fd35566d750b362b0897a26c399136df07bababbde6203ff2954ed4"),
+            (65, "21fe0ceb0052be7fb0f004187cacd7de67fa6eb0938d927677f2398c132317a8"),
+        ];
+
+        let mut key = [0u8; 64];
+        let mut msg = [0u8; 255];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        for (i, byte) in msg.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        for &(len, expected) in b_tests.iter() {
+            let mut engine = blake2b_512::HashEngine::with_params(&key, &[0; 16], &[0; 16]);
+            engine.input(&msg[..len]);
+            assert_eq!(blake2b_512::Hash::from_engine(engine).to_hex(), expected);
+        }
+        for &(len, expected) in s_tests.iter() {
+            let mut engine = blake2s_256::HashEngine::with_params(&key[..32], &[0; 8], &[0; 8]);
+            engine.input(&msg[..len]);
+            assert_eq!(blake2s_256::Hash::from_engine(engine).to_hex(), expected);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn params() {
+        use crate::hex::ToHex;
+
+        // Personalization as used by Zcash for the (empty) prevouts hash of ZIP 243.
+        let engine = blake2b_256::HashEngine::with_params(&[], &[0; 16], b"ZcashPrevoutHash");
+        assert_eq!(
+            blake2b_256::Hash::from_engine(engine).to_hex(),
+            "d53a633bbecf82fe9e9484d8a0e727c73bb9e68c96e72dec30144f6a84afa136",
+        );
+
+        let mut salt = [0u8; 16];
+        for (i, byte) in salt.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let mut engine = blake2b_256::HashEngine::with_params(b"key", &salt, b"personalization!");
+        engine.input(b"abc");
+        assert_eq!(
+            blake2b_256::Hash::from_engine(engine).to_hex(),
+            "22dfcce29f64e9b0c520422d74e5afa5ac7af3995da7b2e86e888105f94f4472",
+        );
+
+        let mut salt = [0u8; 8];
+        salt.copy_from_slice(b"\x00\x01\x02\x03\x04\x05\x06\x07");
+        let mut engine = blake2s_256::HashEngine::with_params(b"key", &salt, b"personal");
+        engine.input(b"abc");
+        assert_eq!(
+            blake2s_256::Hash::from_engine(engine).to_hex(),
+            "06df8c1290c37507c86a83b3b4abdef72121f19309beb7705d4dd2b52a5ea945",
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn variable_length() {
+        use crate::hex::ToHex;
+
+        let mut engine = Blake2b::new(20);
+        engine.input(b"abc");
+        let mut out = [0u8; 20];
+        engine.finalize_into(&mut out);
+        assert_eq!(out.to_hex(), "384264f676f39536840523f284921cdc68b6846b");
+
+        let mut engine = Blake2s::new(16);
+        assert_eq!(engine.out_len(), 16);
+        engine.input(b"abc");
+        let mut out = [0u8; 16];
+        engine.finalize_into(&mut out);
+        assert_eq!(out.to_hex(), "aa4938119b1dc7b87cbad0ffd200d0ae");
+    }
+
+    #[test]
+    #[should_panic]
+    fn output_too_long() {
+        Blake2b::new(65);
+    }
+
+    #[test]
+    #[should_panic]
+    fn key_too_long() {
+        Blake2s::with_params(32, &[0; 33], &[0; 8], &[0; 8]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn blake2b_256_serde() {
+        use serde_test::{Configure, Token, assert_tokens};
+
+        static HASH_BYTES: [u8; 32] = [
+            0xbd, 0xdd, 0x81, 0x3c, 0x63, 0x42, 0x39, 0x72,
+            0x31, 0x71, 0xef, 0x3f, 0xee, 0x98, 0x57, 0x9b,
+            0x94, 0x96, 0x4e, 0x3b, 0xb1, 0xcb, 0x3e, 0x42,
+            0x72, 0x62, 0xc8, 0xc0, 0x68, 0xd5, 0x23, 0x19,
+        ];
+
+        let hash = blake2b_256::Hash::from_slice(&HASH_BYTES).expect("right number of bytes");
+        assert_tokens(&hash.compact(), &[Token::BorrowedBytes(&HASH_BYTES[..])]);
+        assert_tokens(&hash.readable(), &[Token::Str("bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319")]);
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use crate::{Hash, HashEngine};
+    use super::{blake2b_512, blake2s_256};
+
+    #[bench]
+    pub fn blake2b_512_10(bh: &mut Bencher) {
+        let mut engine = blake2b_512::Hash::engine();
+        let bytes = [1u8; 10];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2b_512_1k(bh: &mut Bencher) {
+        let mut engine = blake2b_512::Hash::engine();
+        let bytes = [1u8; 1024];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2b_512_64k(bh: &mut Bencher) {
+        let mut engine = blake2b_512::Hash::engine();
+        let bytes = [1u8; 65536];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2s_256_10(bh: &mut Bencher) {
+        let mut engine = blake2s_256::Hash::engine();
+        let bytes = [1u8; 10];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2s_256_1k(bh: &mut Bencher) {
+        let mut engine = blake2s_256::Hash::engine();
+        let bytes = [1u8; 1024];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake2s_256_64k(bh: &mut Bencher) {
+        let mut engine = blake2s_256::Hash::engine();
+        let bytes = [1u8; 65536];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+}
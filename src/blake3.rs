@@ -0,0 +1,690 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! BLAKE3 implementation.
+//!
+//! Besides the plain hash function, the [`HashEngine`] supports the keyed-hash and derive-key
+//! modes of BLAKE3 and can be finalized into an [`OutputReader`] producing an arbitrary amount
+//! of output. With the `std` feature, [`HashEngine::input_parallel`] hashes large inputs on
+//! several threads.
+//!
+
+use core::str;
+use core::cmp;
+use core::ops::Index;
+use core::slice::SliceIndex;
+
+use crate::{Error, HashEngine as _, hex};
+
+crate::internal_macros::hash_type! {
+    256,
+    false,
+    "Output of the BLAKE3 hash function.",
+    "crate::util::json_hex_string::len_32"
+}
+
+/// Length of a key (for the keyed-hash mode), in bytes.
+pub const KEY_LEN: usize = 32;
+
+const BLOCK_LEN: usize = 64;
+const CHUNK_LEN: usize = 1024;
+
+/// Maximum depth of the chunk tree, enough for inputs of up to 2^64 bytes.
+const MAX_DEPTH: usize = 54;
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+const KEYED_HASH: u32 = 1 << 4;
+const DERIVE_KEY_CONTEXT: u32 = 1 << 5;
+const DERIVE_KEY_MATERIAL: u32 = 1 << 6;
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+#[inline]
+fn g(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(x);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(y);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+/// The BLAKE3 compression function.
+fn compress(cv: &[u32; 8], block_words: &[u32; 16], counter: u64, block_len: u32, flags: u32) -> [u32; 16] {
+    let mut state = [
+        cv[0], cv[1], cv[2], cv[3], cv[4], cv[5], cv[6], cv[7],
+        IV[0], IV[1], IV[2], IV[3], counter as u32, (counter >> 32) as u32, block_len, flags,
+    ];
+    let mut m = *block_words;
+    for round in 0..7 {
+        g(&mut state, 0, 4, 8, 12, m[0], m[1]);
+        g(&mut state, 1, 5, 9, 13, m[2], m[3]);
+        g(&mut state, 2, 6, 10, 14, m[4], m[5]);
+        g(&mut state, 3, 7, 11, 15, m[6], m[7]);
+        g(&mut state, 0, 5, 10, 15, m[8], m[9]);
+        g(&mut state, 1, 6, 11, 12, m[10], m[11]);
+        g(&mut state, 2, 7, 8, 13, m[12], m[13]);
+        g(&mut state, 3, 4, 9, 14, m[14], m[15]);
+
+        if round < 6 {
+            let mut permuted = [0; 16];
+            for (p, idx) in permuted.iter_mut().zip(MSG_PERMUTATION.iter()) {
+                *p = m[*idx];
+            }
+            m = permuted;
+        }
+    }
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= cv[i];
+    }
+    state
+}
+
+fn words_from_le_bytes(bytes: &[u8], words: &mut [u32]) {
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+}
+
+fn first_8_words(words: [u32; 16]) -> [u32; 8] {
+    let mut ret = [0; 8];
+    ret.copy_from_slice(&words[..8]);
+    ret
+}
+
+/// A node of the tree whose compression has not been done yet, because it may be the root.
+#[derive(Clone)]
+struct Output {
+    input_cv: [u32; 8],
+    block_words: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8_words(compress(&self.input_cv, &self.block_words, self.counter, self.block_len, self.flags))
+    }
+
+    fn root_output_block(&self, block_counter: u64) -> [u8; 2 * KEY_LEN] {
+        let words = compress(&self.input_cv, &self.block_words, block_counter, self.block_len, self.flags | ROOT);
+        let mut ret = [0; 2 * KEY_LEN];
+        for (word, out) in words.iter().zip(ret.chunks_exact_mut(4)) {
+            out.copy_from_slice(&word.to_le_bytes());
+        }
+        ret
+    }
+}
+
+fn parent_output(left: &[u32; 8], right: &[u32; 8], key: &[u32; 8], flags: u32) -> Output {
+    let mut block_words = [0; 16];
+    block_words[..8].copy_from_slice(left);
+    block_words[8..].copy_from_slice(right);
+    Output {
+        input_cv: *key,
+        block_words,
+        counter: 0,
+        block_len: BLOCK_LEN as u32,
+        flags: PARENT | flags,
+    }
+}
+
+#[derive(Clone)]
+struct ChunkState {
+    cv: [u32; 8],
+    chunk_counter: u64,
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+impl ChunkState {
+    fn new(key: &[u32; 8], chunk_counter: u64, flags: u32) -> ChunkState {
+        ChunkState {
+            cv: *key,
+            chunk_counter,
+            block: [0; BLOCK_LEN],
+            block_len: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        BLOCK_LEN * self.blocks_compressed + self.block_len
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 { CHUNK_START } else { 0 }
+    }
+
+    fn input(&mut self, mut inp: &[u8]) {
+        while !inp.is_empty() {
+            // The last block of the chunk is compressed with the end flag, so a full block is only
+            // compressed once we know more data follows it.
+            if self.block_len == BLOCK_LEN {
+                let mut block_words = [0; 16];
+                words_from_le_bytes(&self.block, &mut block_words);
+                let flags = self.flags | self.start_flag();
+                self.cv = first_8_words(compress(&self.cv, &block_words, self.chunk_counter, BLOCK_LEN as u32, flags));
+                self.blocks_compressed += 1;
+                self.block = [0; BLOCK_LEN];
+                self.block_len = 0;
+            }
+
+            let n = cmp::min(BLOCK_LEN - self.block_len, inp.len());
+            self.block[self.block_len..self.block_len + n].copy_from_slice(&inp[..n]);
+            self.block_len += n;
+            inp = &inp[n..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        let mut block_words = [0; 16];
+        words_from_le_bytes(&self.block, &mut block_words);
+        Output {
+            input_cv: self.cv,
+            block_words,
+            counter: self.chunk_counter,
+            block_len: self.block_len as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+/// Engine to compute the BLAKE3 hash function.
+#[derive(Clone)]
+pub struct HashEngine {
+    key: [u32; 8],
+    flags: u32,
+    chunk_state: ChunkState,
+    cv_stack: [[u32; 8]; MAX_DEPTH],
+    cv_stack_len: usize,
+    length: usize,
+}
+
+impl Default for HashEngine {
+    fn default() -> Self {
+        HashEngine::new_internal(&IV, 0)
+    }
+}
+
+impl HashEngine {
+    fn new_internal(key: &[u32; 8], flags: u32) -> HashEngine {
+        HashEngine {
+            key: *key,
+            flags,
+            chunk_state: ChunkState::new(key, 0, flags),
+            cv_stack: [[0; 8]; MAX_DEPTH],
+            cv_stack_len: 0,
+            length: 0,
+        }
+    }
+
+    /// Creates an engine for the keyed-hash mode of BLAKE3.
+    pub fn new_keyed(key: &[u8; KEY_LEN]) -> HashEngine {
+        let mut key_words = [0; 8];
+        words_from_le_bytes(key, &mut key_words);
+        HashEngine::new_internal(&key_words, KEYED_HASH)
+    }
+
+    /// Creates an engine for the derive-key mode of BLAKE3.
+    ///
+    /// The context string should be hardcoded, globally unique and application-specific. The key
+    /// material is then given as input to the engine, and the derived key is its output.
+    pub fn new_derive_key(context: &str) -> HashEngine {
+        let mut context_engine = HashEngine::new_internal(&IV, DERIVE_KEY_CONTEXT);
+        context_engine.input(context.as_bytes());
+        let mut context_key = [0; KEY_LEN];
+        context_engine.finalize_xof().squeeze(&mut context_key);
+
+        let mut key_words = [0; 8];
+        words_from_le_bytes(&context_key, &mut key_words);
+        HashEngine::new_internal(&key_words, DERIVE_KEY_MATERIAL)
+    }
+
+    /// Finalizes the engine, returning a reader which produces an arbitrary amount of output.
+    ///
+    /// The first 32 bytes of output are the same as the [`struct@Hash`] of the input.
+    pub fn finalize_xof(self) -> OutputReader {
+        OutputReader {
+            output: self.final_output(),
+            position: 0,
+        }
+    }
+
+    fn push_cv(&mut self, cv: [u32; 8]) {
+        self.cv_stack[self.cv_stack_len] = cv;
+        self.cv_stack_len += 1;
+    }
+
+    fn pop_cv(&mut self) -> [u32; 8] {
+        self.cv_stack_len -= 1;
+        self.cv_stack[self.cv_stack_len]
+    }
+
+    /// Adds the chaining value of a completed subtree, merging it with the subtrees to its left
+    /// which are now complete. `total_subtrees` counts the subtrees of the same size hashed so far,
+    /// including this one.
+    fn add_subtree_cv(&mut self, mut new_cv: [u32; 8], mut total_subtrees: u64) {
+        while total_subtrees & 1 == 0 {
+            new_cv = parent_output(&self.pop_cv(), &new_cv, &self.key, self.flags).chaining_value();
+            total_subtrees >>= 1;
+        }
+        self.push_cv(new_cv);
+    }
+
+    /// Completes the current chunk if it is full, which must only be done when more input follows.
+    fn flush_full_chunk(&mut self) {
+        if self.chunk_state.len() == CHUNK_LEN {
+            let chunk_cv = self.chunk_state.output().chaining_value();
+            let total_chunks = self.chunk_state.chunk_counter + 1;
+            self.add_subtree_cv(chunk_cv, total_chunks);
+            self.chunk_state = ChunkState::new(&self.key, total_chunks, self.flags);
+        }
+    }
+
+    fn final_output(&self) -> Output {
+        let mut output = self.chunk_state.output();
+        for cv in self.cv_stack[..self.cv_stack_len].iter().rev() {
+            output = parent_output(cv, &output.chaining_value(), &self.key, self.flags);
+        }
+        output
+    }
+}
+
+#[cfg(feature = "std")]
+impl HashEngine {
+    /// Adds data to the engine, hashing it on up to `threads` threads.
+    ///
+    /// Data is split in subtrees of 256 KiB which are hashed independently, so this only helps for
+    /// inputs of at least a few MiB; smaller inputs are simply hashed on the current thread. The
+    /// result is the same as with [`input`](crate::HashEngine::input).
+    pub fn input_parallel(&mut self, mut data: &[u8], threads: usize) {
+        use std::thread;
+
+        const SUBTREE_CHUNKS: u64 = 256;
+        const SUBTREE_LEN: usize = SUBTREE_CHUNKS as usize * CHUNK_LEN;
+
+        // Hash up to the next subtree boundary on this thread.
+        let to_boundary = (SUBTREE_LEN - self.length % SUBTREE_LEN) % SUBTREE_LEN;
+        let n = cmp::min(to_boundary, data.len());
+        self.input(&data[..n]);
+        data = &data[n..];
+
+        // The last subtree is kept for this thread, as it may contain the root of the tree.
+        let n_subtrees = data.len().saturating_sub(1) / SUBTREE_LEN;
+        if threads < 2 || n_subtrees < 2 {
+            self.input(data);
+            return;
+        }
+
+        self.flush_full_chunk();
+        let mut total_subtrees = self.chunk_state.chunk_counter / SUBTREE_CHUNKS;
+        // Each thread hashes a copy of one subtree at a time, so that large inputs do not need
+        // as much memory again.
+        for batch in data[..n_subtrees * SUBTREE_LEN].chunks(threads.saturating_mul(SUBTREE_LEN)) {
+            let handles: Vec<_> = batch.chunks(SUBTREE_LEN).enumerate().map(|(i, subtree)| {
+                let subtree = subtree.to_vec();
+                let chunk_counter = (total_subtrees + i as u64) * SUBTREE_CHUNKS;
+                let (key, flags) = (self.key, self.flags);
+                thread::spawn(move || subtree_cv(&subtree, chunk_counter, &key, flags))
+            }).collect();
+
+            for handle in handles {
+                let cv = handle.join().expect("BLAKE3 hashing thread panicked");
+                total_subtrees += 1;
+                self.add_subtree_cv(cv, total_subtrees);
+            }
+        }
+        self.chunk_state = ChunkState::new(&self.key, total_subtrees * SUBTREE_CHUNKS, self.flags);
+        self.length += n_subtrees * SUBTREE_LEN;
+
+        self.input(&data[n_subtrees * SUBTREE_LEN..]);
+    }
+}
+
+/// Computes the chaining value of a complete subtree whose length is a power of two chunks.
+#[cfg(feature = "std")]
+fn subtree_cv(data: &[u8], chunk_counter: u64, key: &[u32; 8], flags: u32) -> [u32; 8] {
+    if data.len() == CHUNK_LEN {
+        let mut chunk_state = ChunkState::new(key, chunk_counter, flags);
+        chunk_state.input(data);
+        chunk_state.output().chaining_value()
+    } else {
+        let half = data.len() / 2;
+        let left = subtree_cv(&data[..half], chunk_counter, key, flags);
+        let right = subtree_cv(&data[half..], chunk_counter + (half / CHUNK_LEN) as u64, key, flags);
+        parent_output(&left, &right, key, flags).chaining_value()
+    }
+}
+
+impl crate::HashEngine for HashEngine {
+    type MidState = [u8; 32];
+
+    /// Returns the chaining value of the chunk currently being hashed.
+    fn midstate(&self) -> [u8; 32] {
+        let mut ret = [0; 32];
+        for (val, ret_bytes) in self.chunk_state.cv.iter().zip(ret.chunks_exact_mut(4)) {
+            ret_bytes.copy_from_slice(&val.to_le_bytes());
+        }
+        ret
+    }
+
+    const BLOCK_SIZE: usize = BLOCK_LEN;
+
+    fn n_bytes_hashed(&self) -> usize {
+        self.length
+    }
+
+    fn input(&mut self, mut inp: &[u8]) {
+        self.length += inp.len();
+        while !inp.is_empty() {
+            self.flush_full_chunk();
+            let n = cmp::min(CHUNK_LEN - self.chunk_state.len(), inp.len());
+            self.chunk_state.input(&inp[..n]);
+            inp = &inp[n..];
+        }
+    }
+}
+
+/// Output stream of a finalized BLAKE3 engine.
+#[derive(Clone)]
+pub struct OutputReader {
+    output: Output,
+    position: u64,
+}
+
+impl OutputReader {
+    /// Fills `out` with the next `out.len()` bytes of output.
+    ///
+    /// Successive calls continue where the previous one stopped, so the output does not depend
+    /// on how it is split between calls.
+    pub fn squeeze(&mut self, mut out: &mut [u8]) {
+        const OUTPUT_BLOCK_LEN: u64 = 2 * KEY_LEN as u64;
+
+        while !out.is_empty() {
+            let block = self.output.root_output_block(self.position / OUTPUT_BLOCK_LEN);
+            let offset = (self.position % OUTPUT_BLOCK_LEN) as usize;
+            let n = cmp::min(block.len() - offset, out.len());
+            out[..n].copy_from_slice(&block[offset..offset + n]);
+            self.position += n as u64;
+            out = &mut out[n..];
+        }
+    }
+}
+
+fn from_engine(e: HashEngine) -> Hash {
+    let mut ret = [0; 32];
+    e.finalize_xof().squeeze(&mut ret);
+    Hash(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Hash, HashEngine};
+    use crate::blake3;
+
+    const TEST_KEY: &[u8; 32] = b"whats the Elvish word for friend";
+    const TEST_CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+
+    fn test_input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::hex::{FromHex, ToHex};
+
+        struct Test {
+            input_len: usize,
+            hash: &'static str,
+            keyed_hash: &'static str,
+            derive_key: &'static str,
+        }
+
+        let tests = vec![
+            // Test vectors from the BLAKE3 team, truncated to 32 bytes. The input is
+            // 0, 1, ..., 250, 0, 1, ... of the given length.
+            Test {
+                input_len: 0,
+                hash: "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+                keyed_hash: "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26",
+                derive_key: "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d",
+            },
+            Test {
+                input_len: 1,
+                hash: "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+                keyed_hash: "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b",
+                derive_key: "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c",
+            },
+            Test {
+                input_len: 1023,
+                hash: "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+                keyed_hash: "c951ecdf03288d0fcc96ee3413563d8a6d3589547f2c2fb36d9786470f1b9d6e",
+                derive_key: "74a16c1c3d44368a86e1ca6df64be6a2f64cce8f09220787450722d85725dea5",
+            },
+            Test {
+                input_len: 1024,
+                hash: "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+                keyed_hash: "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4",
+                derive_key: "7356cd7720d5b66b6d0697eb3177d9f8d73a4a5c5e968896eb6a689684302706",
+            },
+            Test {
+                input_len: 1025,
+                hash: "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+                keyed_hash: "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69",
+                derive_key: "effaa245f065fbf82ac186839a249707c3bddf6d3fdda22d1b95a3c970379bcb",
+            },
+            Test {
+                input_len: 2048,
+                hash: "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+                keyed_hash: "879cf1fa2ea0e79126cb1063617a05b6ad9d0b696d0d757cf053439f60a99dd1",
+                derive_key: "7b2945cb4fef70885cc5d78a87bf6f6207dd901ff239201351ffac04e1088a23",
+            },
+            Test {
+                input_len: 3073,
+                hash: "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+                keyed_hash: "68dede9bef00ba89e43f31a6825f4cf433389fedae75c04ee9f0cf16a427c95a",
+                derive_key: "72613c9ec9ff7e40f8f5c173784c532ad852e827dba2bf85b2ab4b76f7079081",
+            },
+            Test {
+                input_len: 8193,
+                hash: "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3b",
+                keyed_hash: "954a2a75420c8d6547e3ba5b98d963e6fa6491addc8c023189cc519821b4a1f5",
+                derive_key: "af1e0346e389b17c23200270a64aa4e1ead98c61695d917de7d5b00491c9b0f1",
+            },
+            Test {
+                input_len: 31744,
+                hash: "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47",
+                keyed_hash: "efa53b389ab67c593dba624d898d0f7353ab99e4ac9d42302ee64cbf9939a419",
+                derive_key: "39772aef80e0ebe60596361e45b061e8f417429d529171b6764468c22928e28e",
+            },
+            Test {
+                input_len: 102400,
+                hash: "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085",
+                keyed_hash: "1c35d1a5811083fd7119f5d5d1ba027b4d01c0c6c49fb6ff2cf75393ea5db4a7",
+                derive_key: "4652cff7a3f385a6103b5c260fc1593e13c778dbe608efb092fe7ee69df6e9c6",
+            },
+        ];
+
+        for test in tests {
+            let input = test_input(test.input_len);
+
+            let hash = blake3::Hash::hash(&input);
+            assert_eq!(hash, blake3::Hash::from_hex(test.hash).expect("parse hex"));
+            assert_eq!(hash.to_hex(), test.hash);
+
+            let mut engine = blake3::HashEngine::new_keyed(TEST_KEY);
+            engine.input(&input);
+            assert_eq!(blake3::Hash::from_engine(engine).to_hex(), test.keyed_hash);
+
+            let mut engine = blake3::HashEngine::new_derive_key(TEST_CONTEXT);
+            engine.input(&input);
+            assert_eq!(blake3::Hash::from_engine(engine).to_hex(), test.derive_key);
+
+            // Hashing in odd-sized pieces must give the same result
+            let mut engine = blake3::Hash::engine();
+            for chunk in input.chunks(63) {
+                engine.input(chunk);
+            }
+            assert_eq!(engine.n_bytes_hashed(), test.input_len);
+            assert_eq!(blake3::Hash::from_engine(engine).to_hex(), test.hash);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn xof() {
+        use crate::hex::ToHex;
+
+        let mut engine = blake3::Hash::engine();
+        engine.input(&test_input(1024));
+        let mut reader = engine.finalize_xof();
+        let mut out = [0u8; 131];
+        for chunk in out.chunks_mut(10) {
+            reader.squeeze(chunk);
+        }
+        assert_eq!(
+            out.to_hex(),
+            "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7\
+             1cf8107265ecdaf8505b95d8fcec83a98a6a96ea5109d2c179c47a387ffbb404\
+             756f6eeae7883b446b70ebb144527c2075ab8ab204c0086bb22b7c93d465efc5\
+             7f8d917f0b385c6df265e77003b85102967486ed57db5c5ca170ba441427ed9a\
+             fa684e",
+        );
+
+        let mut engine = blake3::HashEngine::new_keyed(TEST_KEY);
+        engine.input(&test_input(1));
+        let mut reader = engine.finalize_xof();
+        let mut out = [0u8; 131];
+        reader.squeeze(&mut out);
+        assert_eq!(
+            out.to_hex(),
+            "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b\
+             6568c0490609413006fbd428eb3fd14e7756d90f73a4725fad147f7bf70fd61c\
+             4e0cf7074885e92b0e3f125978b4154986d4fb202a3f331a3fb6cf349a3a70e4\
+             9990f98fe4289761c8602c4e6ab1138d31d3b62218078b2f3ba9a88e1d08d0dd\
+             4cea11",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn input_parallel() {
+        use crate::hex::ToHex;
+
+        let input = test_input(1_000_000);
+        let mut engine = blake3::Hash::engine();
+        engine.input(&input[..1000]);
+        engine.input_parallel(&input[1000..], 3);
+        assert_eq!(engine.n_bytes_hashed(), input.len());
+        assert_eq!(
+            blake3::Hash::from_engine(engine).to_hex(),
+            "5e82c663d164c54e4fcdfcd70e3ca464662228bdbad45cce2e0c2bff999064ef",
+        );
+
+        let mut engine = blake3::HashEngine::new_keyed(TEST_KEY);
+        engine.input_parallel(&input, 4);
+        assert_eq!(
+            blake3::Hash::from_engine(engine).to_hex(),
+            "6202caf0bdfc1a56b256113502d23eceafbdac9d3bb5c235033d66d46e8a512b",
+        );
+
+        // Inputs ending exactly on a subtree boundary
+        for &len in [0, 1024, 256 * 1024, 3 * 256 * 1024].iter() {
+            let mut engine = blake3::Hash::engine();
+            engine.input_parallel(&input[..len], 2);
+            assert_eq!(blake3::Hash::from_engine(engine), blake3::Hash::hash(&input[..len]));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn blake3_serde() {
+        use serde_test::{Configure, Token, assert_tokens};
+
+        static HASH_BYTES: [u8; 32] = [
+            0x64, 0x37, 0xb3, 0xac, 0x38, 0x46, 0x51, 0x33,
+            0xff, 0xb6, 0x3b, 0x75, 0x27, 0x3a, 0x8d, 0xb5,
+            0x48, 0xc5, 0x58, 0x46, 0x5d, 0x79, 0xdb, 0x03,
+            0xfd, 0x35, 0x9c, 0x6c, 0xd5, 0xbd, 0x9d, 0x85,
+        ];
+
+        let hash = blake3::Hash::from_slice(&HASH_BYTES).expect("right number of bytes");
+        assert_tokens(&hash.compact(), &[Token::BorrowedBytes(&HASH_BYTES[..])]);
+        assert_tokens(&hash.readable(), &[Token::Str("6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85")]);
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use crate::{Hash, HashEngine};
+    use crate::blake3;
+
+    #[bench]
+    pub fn blake3_10(bh: &mut Bencher) {
+        let mut engine = blake3::Hash::engine();
+        let bytes = [1u8; 10];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake3_1k(bh: &mut Bencher) {
+        let mut engine = blake3::Hash::engine();
+        let bytes = [1u8; 1024];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake3_64k(bh: &mut Bencher) {
+        let mut engine = blake3::Hash::engine();
+        let bytes = [1u8; 65536];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn blake3_16m_parallel(bh: &mut Bencher) {
+        let mut engine = blake3::Hash::engine();
+        let bytes = vec![1u8; 16 << 20];
+        bh.iter( || {
+            engine.input_parallel(&bytes, 4);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+}
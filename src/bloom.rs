@@ -0,0 +1,287 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! BIP37 bloom filters.
+//!
+//! These are the filters sent by SPV clients in `filterload` messages, built on the
+//! [`murmur3`](crate::murmur3) hash function.
+//!
+
+use crate::alloc::vec::Vec;
+use crate::murmur3;
+
+/// Maximum size of a filter accepted by Bitcoin Core, in bytes.
+pub const MAX_BLOOM_FILTER_SIZE: usize = 36_000;
+
+/// Maximum number of hash functions of a filter accepted by Bitcoin Core.
+pub const MAX_HASH_FUNCS: u32 = 50;
+
+/// Multiplier of the hash function index in the MurmurHash3 seed.
+const SEED_MULTIPLIER: u32 = 0xfba4c795;
+
+/// How a node should update a filter when it matches a transaction output.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BloomFlags {
+    /// Never update the filter.
+    None,
+    /// Add the outpoint of every matched output to the filter.
+    All,
+    /// Only add the outpoints of matched pay-to-pubkey and bare multisig outputs.
+    P2PubkeyOnly,
+}
+
+impl BloomFlags {
+    /// Returns the value of the flags in a `filterload` message.
+    pub fn to_u8(self) -> u8 {
+        match self {
+            BloomFlags::None => 0,
+            BloomFlags::All => 1,
+            BloomFlags::P2PubkeyOnly => 2,
+        }
+    }
+
+    /// Parses the flags of a `filterload` message, ignoring the bits which have no meaning (like
+    /// Bitcoin Core does).
+    pub fn from_u8(flags: u8) -> Option<BloomFlags> {
+        match flags & 3 {
+            0 => Some(BloomFlags::None),
+            1 => Some(BloomFlags::All),
+            2 => Some(BloomFlags::P2PubkeyOnly),
+            _ => None,
+        }
+    }
+}
+
+/// A BIP37 bloom filter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BloomFilter {
+    data: Vec<u8>,
+    n_hash_funcs: u32,
+    tweak: u32,
+    flags: BloomFlags,
+}
+
+impl BloomFilter {
+    /// Creates an empty filter sized for `n_elements` elements with a false positive rate of
+    /// `fp_rate`, computing its size and number of hash functions exactly like Bitcoin Core.
+    ///
+    /// The filter is capped at [`MAX_BLOOM_FILTER_SIZE`] bytes and [`MAX_HASH_FUNCS`] hash
+    /// functions, so the false positive rate is higher for very large sets.
+    ///
+    /// # Panics
+    ///
+    /// If `n_elements` is zero.
+    #[cfg(feature = "std")]
+    pub fn new(n_elements: u32, fp_rate: f64, tweak: u32, flags: BloomFlags) -> BloomFilter {
+        const LN2SQUARED: f64 = core::f64::consts::LN_2 * core::f64::consts::LN_2;
+
+        assert!(n_elements > 0, "a bloom filter needs at least one element");
+
+        let n_bits = -1.0 / LN2SQUARED * f64::from(n_elements) * fp_rate.ln();
+        let size = ((n_bits as u64).min(MAX_BLOOM_FILTER_SIZE as u64 * 8) / 8) as usize;
+        // Integer division first, as in Bitcoin Core.
+        let bits_per_element = (size * 8 / n_elements as usize) as f64;
+        let n_hash_funcs = ((bits_per_element * core::f64::consts::LN_2) as u32).min(MAX_HASH_FUNCS);
+
+        BloomFilter::from_parts(vec![0; size], n_hash_funcs, tweak, flags)
+    }
+
+    /// Creates a filter from its raw parts, as found in a `filterload` message.
+    pub fn from_parts(data: Vec<u8>, n_hash_funcs: u32, tweak: u32, flags: BloomFlags) -> BloomFilter {
+        BloomFilter {
+            data,
+            n_hash_funcs,
+            tweak,
+            flags,
+        }
+    }
+
+    /// Returns the bit field of the filter.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the number of hash functions of the filter.
+    pub fn n_hash_funcs(&self) -> u32 {
+        self.n_hash_funcs
+    }
+
+    /// Returns the tweak added to the seed of the hash functions.
+    pub fn tweak(&self) -> u32 {
+        self.tweak
+    }
+
+    /// Returns the update flags of the filter.
+    pub fn flags(&self) -> BloomFlags {
+        self.flags
+    }
+
+    /// Whether the filter is small enough to be accepted by Bitcoin Core.
+    pub fn is_within_size_constraints(&self) -> bool {
+        self.data.len() <= MAX_BLOOM_FILTER_SIZE && self.n_hash_funcs <= MAX_HASH_FUNCS
+    }
+
+    fn bit_index(&self, hash_num: u32, data: &[u8]) -> usize {
+        let seed = hash_num.wrapping_mul(SEED_MULTIPLIER).wrapping_add(self.tweak);
+        murmur3::Hash::hash_to_u32_with_seed(seed, data) as usize % (self.data.len() * 8)
+    }
+
+    /// Adds an element to the filter.
+    ///
+    /// This does nothing on a filter without any data.
+    pub fn insert(&mut self, data: &[u8]) {
+        if self.data.is_empty() {
+            return;
+        }
+        for i in 0..self.n_hash_funcs {
+            let index = self.bit_index(i, data);
+            self.data[index >> 3] |= 1 << (index & 7);
+        }
+    }
+
+    /// Checks whether an element may be in the filter.
+    ///
+    /// There are no false negatives, but there can be false positives; a filter without any data
+    /// matches everything.
+    pub fn contains(&self, data: &[u8]) -> bool {
+        if self.data.is_empty() {
+            return true;
+        }
+        (0..self.n_hash_funcs).all(|i| {
+            let index = self.bit_index(i, data);
+            self.data[index >> 3] & (1 << (index & 7)) != 0
+        })
+    }
+
+    /// Serializes the filter as the payload of a `filterload` message.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(self.data.len() + 18);
+        let len = self.data.len() as u64;
+        match len {
+            0..=0xfc => ret.push(len as u8),
+            0xfd..=0xffff => {
+                ret.push(0xfd);
+                ret.extend_from_slice(&(len as u16).to_le_bytes());
+            }
+            0x10000..=0xffffffff => {
+                ret.push(0xfe);
+                ret.extend_from_slice(&(len as u32).to_le_bytes());
+            }
+            _ => {
+                ret.push(0xff);
+                ret.extend_from_slice(&len.to_le_bytes());
+            }
+        }
+        ret.extend_from_slice(&self.data);
+        ret.extend_from_slice(&self.n_hash_funcs.to_le_bytes());
+        ret.extend_from_slice(&self.tweak.to_le_bytes());
+        ret.push(self.flags.to_u8());
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use crate::hex::{FromHex, ToHex};
+    #[cfg(feature = "std")]
+    use crate::{hash160, Hash};
+
+    // Test vectors from Bitcoin Core's bloom_tests.cpp
+
+    #[cfg(feature = "std")]
+    fn insert_serialize(tweak: u32, expected: &str) {
+        let mut filter = BloomFilter::new(3, 0.01, tweak, BloomFlags::All);
+
+        let first = Vec::from_hex("99108ad8ed9bb6274d3980bab5a85c048f0950c8").unwrap();
+        assert!(!filter.contains(&first), "Bloom filter should be empty!");
+        filter.insert(&first);
+        assert!(filter.contains(&first), "Bloom filter doesn't contain just-inserted object!");
+        // One bit different in first byte
+        let different = Vec::from_hex("19108ad8ed9bb6274d3980bab5a85c048f0950c8").unwrap();
+        assert!(!filter.contains(&different), "Bloom filter contains something it shouldn't!");
+
+        let second = Vec::from_hex("b5a2c786d9ef4658287ced5914b37a1b4aa32eee").unwrap();
+        filter.insert(&second);
+        assert!(filter.contains(&second), "Bloom filter doesn't contain just-inserted object (2)!");
+
+        let third = Vec::from_hex("b9300670b4c5366e95b2699e8b18bc75e5f729c5").unwrap();
+        filter.insert(&third);
+        assert!(filter.contains(&third), "Bloom filter doesn't contain just-inserted object (3)!");
+
+        assert_eq!(filter.serialize().to_hex(), expected);
+        assert!(filter.is_within_size_constraints());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bloom_create_insert_serialize() {
+        insert_serialize(0, "03614e9b050000000000000001");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bloom_create_insert_serialize_with_tweak() {
+        // Same test as bloom_create_insert_serialize, but we add a nTweak of 100
+        insert_serialize(2147483649, "03ce4299050000000100008001");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bloom_create_insert_key() {
+        // Uncompressed public key of 5Kg1gnAjaLfKiwhhPpGS3QfRg2m6awQvaj98JCZBZQ5SuS2F15C
+        let pubkey = Vec::from_hex(
+            "045b81f0017e2091e2edcd5eecf10d5bdd120a5514cb3ee65b8447ec18bfc4575c\
+             6d5bf415e54e03b1067934a0f0ba76b01c6b9ab227142ee1d543764b69d901e0"
+        ).unwrap();
+
+        let mut filter = BloomFilter::new(2, 0.001, 0, BloomFlags::All);
+        filter.insert(&pubkey);
+        filter.insert(&hash160::Hash::hash(&pubkey)[..]);
+
+        assert_eq!(filter.serialize().to_hex(), "038fc16b080000000000000001");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bloom_sizes() {
+        // Capped at the maximum size, which leaves too few bits per element for any hash function
+        let filter = BloomFilter::new(1_000_000, 0.000_001, 0, BloomFlags::None);
+        assert_eq!(filter.data().len(), MAX_BLOOM_FILTER_SIZE);
+        assert_eq!(filter.n_hash_funcs(), 0);
+        // Capped at the maximum number of hash functions
+        let filter = BloomFilter::new(1, 1e-30, 0, BloomFlags::None);
+        assert_eq!(filter.n_hash_funcs(), MAX_HASH_FUNCS);
+
+        // An empty filter matches everything
+        let mut filter = BloomFilter::from_parts(vec![], 10, 0, BloomFlags::None);
+        filter.insert(b"foo");
+        assert!(filter.contains(b"bar"));
+        assert!(filter.is_within_size_constraints());
+
+        let filter = BloomFilter::from_parts(vec![0; MAX_BLOOM_FILTER_SIZE + 1], 10, 0, BloomFlags::None);
+        assert!(!filter.is_within_size_constraints());
+        assert_eq!(filter.serialize()[..3], [0xfd, 0xa1, 0x8c]);
+    }
+
+    #[test]
+    fn bloom_flags() {
+        for flags in [BloomFlags::None, BloomFlags::All, BloomFlags::P2PubkeyOnly].iter() {
+            assert_eq!(BloomFlags::from_u8(flags.to_u8()), Some(*flags));
+        }
+        assert_eq!(BloomFlags::from_u8(0x81), Some(BloomFlags::All));
+        assert_eq!(BloomFlags::from_u8(3), None);
+    }
+}
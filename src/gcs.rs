@@ -0,0 +1,573 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! BIP158 Golomb-coded sets.
+//!
+//! These are the compact block filters served to light clients by BIP157 nodes. Selecting the
+//! elements of a filter (e.g. the output scripts of a block and the scripts they spend for a
+//! basic filter) is left to the caller; this module only encodes and queries the set.
+//!
+
+use core::fmt;
+
+use crate::alloc::vec::Vec;
+use crate::{sha256d, siphash24, Hash, HashEngine};
+
+/// Golomb-Rice coding parameter of basic filters.
+pub const P: u8 = 19;
+
+/// Inverse of the false positive rate of basic filters.
+pub const M: u64 = 784_931;
+
+hash_newtype!(FilterHash, sha256d::Hash, 32, doc="Hash of a BIP158 filter.");
+hash_newtype!(FilterHeader, sha256d::Hash, 32, doc="BIP157 filter header, committing to a filter and all the filters before it.");
+
+impl FilterHash {
+    /// Computes the header of the filter with this hash, given the header of the filter of the
+    /// previous block.
+    pub fn filter_header(&self, previous: &FilterHeader) -> FilterHeader {
+        let mut engine = FilterHeader::engine();
+        engine.input(&self[..]);
+        engine.input(&previous[..]);
+        FilterHeader::from_engine(engine)
+    }
+}
+
+/// Filter decoding error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The filter ended before all of its elements were decoded.
+    UnexpectedEof,
+    /// The number of elements of the filter is not encoded in its shortest form.
+    NonMinimalVarInt,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnexpectedEof => f.write_str("unexpected end of filter"),
+            Error::NonMinimalVarInt => f.write_str("non-minimal varint"),
+        }
+    }
+}
+
+/// Returns the SipHash keys of the filter of the block with hash `block_hash`.
+///
+/// These are the first 16 bytes of the hash, in internal byte order.
+pub fn block_keys(block_hash: &sha256d::Hash) -> (u64, u64) {
+    let mut k0 = [0; 8];
+    let mut k1 = [0; 8];
+    k0.copy_from_slice(&block_hash[0..8]);
+    k1.copy_from_slice(&block_hash[8..16]);
+    (u64::from_le_bytes(k0), u64::from_le_bytes(k1))
+}
+
+/// Maps an element uniformly to `[0, f)`.
+fn hash_to_range(k0: u64, k1: u64, element: &[u8], f: u64) -> u64 {
+    let hash = siphash24::Hash::hash_to_u64_with_keys(k0, k1, element);
+    ((u128::from(hash) * u128::from(f)) >> 64) as u64
+}
+
+/// A BIP158 Golomb-coded set.
+///
+/// This holds the serialized filter: the number of elements as a compact size, followed by the
+/// Golomb-Rice coded deltas between the sorted hashed elements.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GcsFilter {
+    content: Vec<u8>,
+}
+
+impl GcsFilter {
+    /// Builds the filter of a set of elements, with the SipHash keys of the block.
+    ///
+    /// Duplicate elements are only added once.
+    pub fn new<I>(k0: u64, k1: u64, elements: I) -> GcsFilter
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut elements: Vec<I::Item> = elements.into_iter().collect();
+        elements.sort_unstable_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        elements.dedup_by(|a, b| a.as_ref() == b.as_ref());
+
+        let n = elements.len() as u64;
+        let f = n * M;
+        let mut values: Vec<u64> = elements.iter().map(|e| hash_to_range(k0, k1, e.as_ref(), f)).collect();
+        values.sort_unstable();
+
+        let mut writer = BitWriter::new(n);
+        let mut last = 0;
+        for value in values {
+            writer.write_golomb_rice(value - last);
+            last = value;
+        }
+        GcsFilter { content: writer.finish() }
+    }
+
+    /// Wraps a serialized filter, as found in a `cfilter` message.
+    ///
+    /// The content is only parsed when the filter is queried.
+    pub fn from_content(content: Vec<u8>) -> GcsFilter {
+        GcsFilter { content }
+    }
+
+    /// Returns the serialized filter.
+    pub fn content(&self) -> &[u8] {
+        &self.content
+    }
+
+    /// Returns the serialized filter, consuming `self`.
+    pub fn into_content(self) -> Vec<u8> {
+        self.content
+    }
+
+    /// Returns the number of elements in the filter.
+    pub fn n_elements(&self) -> Result<u64, Error> {
+        BitReader::new(&self.content).map(|(n, _)| n)
+    }
+
+    /// Returns the hash of the filter.
+    pub fn filter_hash(&self) -> FilterHash {
+        FilterHash::hash(&self.content)
+    }
+
+    /// Computes the header of the filter, given the header of the filter of the previous block.
+    pub fn filter_header(&self, previous: &FilterHeader) -> FilterHeader {
+        self.filter_hash().filter_header(previous)
+    }
+
+    /// Checks whether any of the `query` elements may be in the filter.
+    pub fn match_any<I>(&self, k0: u64, k1: u64, query: I) -> Result<bool, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.matches(k0, k1, query, false)
+    }
+
+    /// Checks whether all of the `query` elements may be in the filter.
+    pub fn match_all<I>(&self, k0: u64, k1: u64, query: I) -> Result<bool, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.matches(k0, k1, query, true)
+    }
+
+    fn matches<I>(&self, k0: u64, k1: u64, query: I, all: bool) -> Result<bool, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let (n, mut reader) = BitReader::new(&self.content)?;
+        let f = n * M;
+        let mut query: Vec<u64> = query.into_iter().map(|q| hash_to_range(k0, k1, q.as_ref(), f)).collect();
+        query.sort_unstable();
+        query.dedup();
+
+        // Walk both sorted lists at once.
+        let mut query = query.into_iter().peekable();
+        let mut value = 0u64;
+        for _ in 0..n {
+            value = value.wrapping_add(reader.read_golomb_rice()?);
+            while let Some(&q) = query.peek() {
+                if q > value {
+                    break;
+                }
+                if (q == value) != all {
+                    return Ok(!all);
+                }
+                query.next();
+            }
+            if query.peek().is_none() {
+                return Ok(all);
+            }
+        }
+        Ok(all && query.peek().is_none())
+    }
+}
+
+/// Writes a filter, most significant bit first.
+struct BitWriter {
+    content: Vec<u8>,
+    buffer: u8,
+    n_bits: u8,
+}
+
+impl BitWriter {
+    fn new(n_elements: u64) -> BitWriter {
+        let mut content = Vec::new();
+        match n_elements {
+            0..=0xfc => content.push(n_elements as u8),
+            0xfd..=0xffff => {
+                content.push(0xfd);
+                content.extend_from_slice(&(n_elements as u16).to_le_bytes());
+            }
+            0x10000..=0xffffffff => {
+                content.push(0xfe);
+                content.extend_from_slice(&(n_elements as u32).to_le_bytes());
+            }
+            _ => {
+                content.push(0xff);
+                content.extend_from_slice(&n_elements.to_le_bytes());
+            }
+        }
+        BitWriter { content, buffer: 0, n_bits: 0 }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        self.buffer = (self.buffer << 1) | bit as u8;
+        self.n_bits += 1;
+        if self.n_bits == 8 {
+            self.content.push(self.buffer);
+            self.buffer = 0;
+            self.n_bits = 0;
+        }
+    }
+
+    fn write_golomb_rice(&mut self, value: u64) {
+        for _ in 0..(value >> P) {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+        for i in (0..P).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.n_bits > 0 {
+            self.content.push(self.buffer << (8 - self.n_bits));
+        }
+        self.content
+    }
+}
+
+/// Reads a filter, most significant bit first.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Parses the number of elements and returns a reader positioned after it.
+    fn new(content: &'a [u8]) -> Result<(u64, BitReader<'a>), Error> {
+        let (&prefix, rest) = content.split_first().ok_or(Error::UnexpectedEof)?;
+        let (width, min) = match prefix {
+            0xfd => (2, 0xfd),
+            0xfe => (4, 0x10000),
+            0xff => (8, 0x100000000),
+            n => return Ok((u64::from(n), BitReader { data: rest, bit: 0 })),
+        };
+        if rest.len() < width {
+            return Err(Error::UnexpectedEof);
+        }
+        let mut bytes = [0; 8];
+        bytes[..width].copy_from_slice(&rest[..width]);
+        let n = u64::from_le_bytes(bytes);
+        if n < min {
+            return Err(Error::NonMinimalVarInt);
+        }
+        // Every element takes more than `P` bits, so this bounds `n * M` as well.
+        if n > (rest.len() - width) as u64 * 8 / u64::from(P) {
+            return Err(Error::UnexpectedEof);
+        }
+        Ok((n, BitReader { data: &rest[width..], bit: 0 }))
+    }
+
+    fn read_bit(&mut self) -> Result<bool, Error> {
+        let byte = self.data.get(self.bit / 8).ok_or(Error::UnexpectedEof)?;
+        let bit = (byte >> (7 - self.bit % 8)) & 1 == 1;
+        self.bit += 1;
+        Ok(bit)
+    }
+
+    fn read_golomb_rice(&mut self) -> Result<u64, Error> {
+        let mut quotient = 0u64;
+        while self.read_bit()? {
+            quotient += 1;
+        }
+        let mut value = quotient << P;
+        for i in (0..P).rev() {
+            value |= (self.read_bit()? as u64) << i;
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::{FromHex, ToHex};
+
+    // Testnet genesis block, from the BIP158 test vectors
+    const GENESIS_HASH: &str = "000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943";
+    const GENESIS_SCRIPT: &str = "4104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac";
+    const GENESIS_FILTER: &str = "019dfca8";
+    const GENESIS_HEADER: &str = "21584579b7eb08997773e5aeff3a7f932700042d0ed2a6129012b7d7ae81b750";
+
+    #[test]
+    fn bip158_genesis() {
+        let block_hash = GENESIS_HASH.parse::<sha256d::Hash>().unwrap();
+        let (k0, k1) = block_keys(&block_hash);
+        let script = Vec::from_hex(GENESIS_SCRIPT).unwrap();
+
+        let filter = GcsFilter::new(k0, k1, &[&script]);
+        assert_eq!(filter.content().to_hex(), GENESIS_FILTER);
+        assert_eq!(filter.n_elements(), Ok(1));
+        assert_eq!(filter.filter_header(&FilterHeader::all_zeros()).to_string(), GENESIS_HEADER);
+
+        assert_eq!(filter.match_any(k0, k1, &[&script[..], b"foo"]), Ok(true));
+        assert_eq!(filter.match_all(k0, k1, &[&script[..], b"foo"]), Ok(false));
+        assert_eq!(filter.match_all(k0, k1, &[&script]), Ok(true));
+        assert_eq!(filter.match_any(k0, k1, &[b"foo"]), Ok(false));
+        // Keyed with another block
+        assert_eq!(filter.match_any(k0 ^ 1, k1, &[&script]), Ok(false));
+    }
+
+    /// A block of the BIP158 test vectors, with the output scripts parsed out of the block.
+    struct Vector {
+        block_hash: &'static str,
+        output_scripts: &'static [&'static str],
+        spent_scripts: &'static [&'static str],
+        previous_header: &'static str,
+        filter: &'static str,
+        header: &'static str,
+    }
+
+    #[test]
+    fn bip158_vectors() {
+        // Testnet blocks from testnet-19.json of BIP158, after the genesis block
+        let vectors = [
+        Vector {
+            // Block 2
+            block_hash: "000000006c02c8ea6e4ff69651f7fcde348fb9d557a06e6957b65552002a7820",
+            output_scripts: &[
+                "21038a7f6ef1c8ca0c588aa53fa860128077c9e6c11e6830f4d7ee4e763a56b7718fac",
+            ],
+            spent_scripts: &[],
+            previous_header: "d7bdac13a59d745b1add0d2ce852f1a0442e8945fc1bf3848d3cbffd88c24fe1",
+            filter: "0174a170",
+            header: "186afd11ef2b5e7e3504f2e8cbf8df28a1fd251fe53d60dff8b1467d1b386cf0",
+        },
+        Vector {
+            // Block 3
+            block_hash: "000000008b896e272758da5297bcd98fdc6d97c9b765ecec401e286dc1fdbe10",
+            output_scripts: &[
+                "2103f6d9ff4c12959445ca5549c811683bf9c88e637b222dd2e0311154c4c85cf423ac",
+            ],
+            spent_scripts: &[],
+            previous_header: "186afd11ef2b5e7e3504f2e8cbf8df28a1fd251fe53d60dff8b1467d1b386cf0",
+            filter: "016cf7a0",
+            header: "8d63aadf5ab7257cb6d2316a57b16f517bff1c6388f124ec4c04af1212729d2a",
+        },
+        Vector {
+            // Block 15007, tx has non-standard OP_RETURN output followed by opcodes
+            block_hash: "0000000038c44c703bae0f98cdd6bf30922326340a5996cc692aaae8bacf47ad",
+            output_scripts: &[
+                "2103f268e9ae07e0f8cb2f6e901d87c510d650b97230c0365b021df8f467363cafb1ac",
+            ],
+            spent_scripts: &[],
+            previous_header: "18b5c2b0146d2d09d24fb00ff5b52bd0742f36c9e65527abdb9de30c027a4748",
+            filter: "013c3710",
+            header: "07384b01311867949e0c046607c66b7a766d338474bb67f66c8ae9dbd454b20e",
+        },
+        Vector {
+            // Block 49291, tx pays to empty output script
+            block_hash: "0000000018b07dca1b28b4b5a119f6d6e71698ce1ed96f143f54179ce177a19c",
+            output_scripts: &[
+                "2102971dd6034ed0cf52450b608d196c07d6345184fcb14deb277a6b82d526a6163dac",
+                "76a91445db0b779c0b9fa207f12a8218c94fc77aff504588ac",
+                "",
+            ],
+            spent_scripts: &[
+                "5221033423007d8f263819a2e42becaaf5b06f34cb09919e06304349d950668209eaed21021d69e2\
+                 b68c3960903b702af7829fadcd80bd89b158150c85c4a75b2c8cb9c39452ae",
+                "52210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f8179821021d69e2\
+                 b68c3960903b702af7829fadcd80bd89b158150c85c4a75b2c8cb9c39452ae",
+                "522102a7ae1e0971fc1689bd66d2a7296da3a1662fd21a53c9e38979e0f090a375c12d21022adb62\
+                 335f41eb4e27056ac37d462cda5ad783fa8e0e526ed79c752475db285d52ae",
+                "52210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f8179821022adb62\
+                 335f41eb4e27056ac37d462cda5ad783fa8e0e526ed79c752475db285d52ae",
+                "512103b9d1d0e2b4355ec3cdef7c11a5c0beff9e8b8d8372ab4b4e0aaf30e80173001951ae",
+                "76a9149144761ebaccd5b4bbdc2a35453585b5637b2f8588ac",
+                "522103f1848b40621c5d48471d9784c8174ca060555891ace6d2b03c58eece946b1a9121020ee5d3\
+                 2b54d429c152fdc7b1db84f2074b0564d35400d89d11870f9273ec140c52ae",
+                "76a914f4fa1cc7de742d135ea82c17adf0bb9cf5f4fb8388ac",
+            ],
+            previous_header: "ed47705334f4643892ca46396eb3f4196a5e30880589e4009ef38eae895d4a13",
+            filter: "0afbc2920af1b027f31f87b592276eb4c32094bb4d3697021b4c6380",
+            header: "b6d98692cec5145f67585f3434ec3c2b3030182e1cb3ec58b855c5c164dfaaa3",
+        },
+        Vector {
+            // Block 180480, tx spends from empty output script
+            block_hash: "00000000fd3ceb2404ff07a785c7fdcc76619edc8ed61bd25134eaa22084366a",
+            output_scripts: &[
+                "2102e769e60137a4df6b0df8ebd387cca44c4c57ae74cc0114a8e8317c8f3bfd85e9ac",
+                "2103bb52138972c48a132fc1f637858c5189607dd0f7fe40c4f20f6ad65f2d389ba4ac",
+                "76a9147779b7fba1c1e06b717069b80ca170e8b04458a488ac",
+                "76a9142a0307cd925dbb66b534c4db33003dd18c57015788ac",
+                "76a914ae19d27efe12f5a886dc79af37ad6805db6f922d88ac",
+                "76a9143b8d051d37a07ea1042067e93efe63dbf73920b988ac",
+                "76a914797fb8777d7991d8284d88bfd421ce520f0f843188ac",
+                "76a9146d10f3f592699265d10b106eda37c3ce793f7a8588ac",
+            ],
+            spent_scripts: &[
+                "",
+                "",
+                "",
+                "76a9142903b138c24be9e070b3e73ec495d77a204615e788ac",
+                "76a91433a1941fd9a37b9821d376f5a51bd4b52fa50e2888ac",
+                "76a914e4374e8155d0865742ca12b8d4d14d41b57d682f88ac",
+                "76a914001fa7459a6cfc64bdc178ba7e7a21603bb2568f88ac",
+                "76a914f6039952bc2b307aeec5371bfb96b66078ec17f688ac",
+            ],
+            previous_header: "d34ef98386f413769502808d4bac5f20f8dfd5bffc9eedafaa71de0eb1f01489",
+            filter: "0db414c859a07e8205876354a210a75042d0463404913d61a8e068e58a3ae2aa080026",
+            header: "c582d51c0ca365e3fcf36c51cb646d7f83a67e867cb4743fd2128e3e022b700c",
+        },
+        Vector {
+            // Block 926485, duplicate pushdata 913bcc2be49cb534c20474c4dee1e9c4c317e7eb
+            block_hash: "000000000000015d6077a411a8f5cc95caf775ccf11c54e27df75ce58d187313",
+            output_scripts: &[
+                "76a914876fbb82ec05caa6af7a3b5e5a983aae6c6cc6d688ac",
+                "6a24aa21a9ed5c748e121c0fe146d973a4ac26fa4a68b0549d46ee22d25f50a5e46fe1b377ee",
+                "52534b424c4f434b3acd16772ad61a3c5f00287480b720f6035d5e54c9efc71be94bb5e3727f1090\
+                 90",
+                "76a9143ebc40e411ed3c76f86711507ab952300890397288ac",
+                "76a91450333046115eaa0ac9e0216565f945070e44573988ac",
+                "76a914c01a7ca16b47be50cbdbc60724f701d52d75156688ac",
+                "a914b7e6f7ff8658b2d1fb107e3d7be7af4742e6b1b387",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+                "a9148fc37ad460fdfbd2b44fe446f6e3071a4f64faa687",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+            ],
+            spent_scripts: &[
+                "a914feb8a29635c56d9cd913122f90678756bf23887687",
+                "76a914c01a7ca16b47be50cbdbc60724f701d52d75156688ac",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+                "76a914913bcc2be49cb534c20474c4dee1e9c4c317e7eb88ac",
+            ],
+            previous_header: "8f13b9a9c85611635b47906c3053ac53cfcec7211455d4cb0d63dc9acc13d472",
+            filter: "09027acea61b6cc3fb33f5d52f7d088a6b2f75d234e89ca800",
+            header: "546c574a0472144bcaf9b6aeabf26372ad87c7af7d1ee0dbfae5e099abeae49c",
+        },
+        Vector {
+            // Block 987876, coinbase tx has unparseable output script
+            block_hash: "0000000000000c00901f2049055e2a437c819d79a3d54fd63e6af796cd7b8a79",
+            output_scripts: &[
+                "76a914c486de584a735ec2f22da7cd9681614681f92173d83d0aa68688ac",
+            ],
+            spent_scripts: &[],
+            previous_header: "fe4d230dbb0f4fec9bed23a5283e08baf996e3f32b93f52c7de1f641ddfd04ad",
+            filter: "010c0b40",
+            header: "0965a544743bbfa36f254446e75630c09404b3d164a261892372977538928ed5",
+        },
+        Vector {
+            // Block 1263442, includes witness data
+            block_hash: "000000006f27ddfe1dd680044a34548f41bed47eba9e6f0b310da21423bc5f33",
+            output_scripts: &[
+                "76a914f2c25ac3d59f3d674b1d1d0a25c27339aaac0ba688ac",
+                "6a24aa21a9edcb26cb3052426b9ebb4d19c819ef87c19677bbf3a7c46ef0855bd1b2abe83491",
+                "001446c29eabe8208a33aa1023c741fa79aa92e881ff",
+            ],
+            spent_scripts: &[
+                "002027a5000c7917f785d8fc6e5a55adfca8717ecb973ebb7743849ff956d896a7ed",
+            ],
+            previous_header: "31d66d516a9eda7de865df29f6ef6cb8e4bf9309e5dac899968a9a62a5df61e3",
+            filter: "0385acb4f0fe889ef0",
+            header: "4e6d564c2a2452065c205dd7eb2791124e0c4e0dbb064c410c24968572589dec",
+        },
+        Vector {
+            // Block 1414221, empty data
+            block_hash: "0000000000000027b2b3b3381f114f674f481544ff2be37ae3788d7e078383b1",
+            output_scripts: &[
+                "",
+            ],
+            spent_scripts: &[],
+            previous_header: "5e5e12d90693c8e936f01847859404c67482439681928353ca1296982042864e",
+            filter: "00",
+            header: "021e8882ef5a0ed932edeebbecfeda1d7ce528ec7b3daa27641acf1189d7b5dc",
+        },
+        ];
+
+        for vector in vectors.iter() {
+            let block_hash = vector.block_hash.parse::<sha256d::Hash>().unwrap();
+            let (k0, k1) = block_keys(&block_hash);
+
+            // Basic filters skip empty and OP_RETURN output scripts, and empty spent scripts
+            let mut elements = Vec::new();
+            let mut op_returns = Vec::new();
+            for script in vector.output_scripts.iter().map(|s| Vec::from_hex(s).unwrap()) {
+                match script.first() {
+                    None => {}
+                    Some(&0x6a) => op_returns.push(script),
+                    Some(_) => elements.push(script),
+                }
+            }
+            for script in vector.spent_scripts.iter().filter(|s| !s.is_empty()) {
+                elements.push(Vec::from_hex(script).unwrap());
+            }
+
+            let filter = GcsFilter::new(k0, k1, &elements);
+            assert_eq!(filter.content().to_hex(), vector.filter, "block {}", vector.block_hash);
+            let previous = vector.previous_header.parse::<FilterHeader>().unwrap();
+            assert_eq!(filter.filter_header(&previous).to_string(), vector.header);
+
+            // Duplicates are only counted once
+            let mut unique = elements.clone();
+            unique.sort();
+            unique.dedup();
+            let decoded = GcsFilter::from_content(Vec::from_hex(vector.filter).unwrap());
+            assert_eq!(decoded.n_elements(), Ok(unique.len() as u64));
+            assert_eq!(decoded.match_all(k0, k1, &elements), Ok(true));
+            for element in &elements {
+                assert_eq!(decoded.match_any(k0, k1, &[element]), Ok(true));
+                assert_eq!(decoded.match_any(k0, k1, &[&element[..], b"foo"]), Ok(true));
+            }
+            assert_eq!(decoded.match_any(k0, k1, &op_returns), Ok(false));
+        }
+
+        // Consecutive blocks chain their headers
+        assert_eq!(vectors[0].header, vectors[1].previous_header);
+    }
+
+    #[test]
+    fn empty_filter() {
+        let filter = GcsFilter::new(0, 0, &[] as &[&[u8]]);
+        assert_eq!(filter.content(), &[0]);
+        assert_eq!(filter.n_elements(), Ok(0));
+        assert_eq!(filter.match_any(0, 0, &[b"foo"]), Ok(false));
+        assert_eq!(filter.match_all(0, 0, &[b"foo"]), Ok(false));
+        assert_eq!(filter.match_all(0, 0, &[] as &[&[u8]]), Ok(true));
+    }
+
+    #[test]
+    fn malformed() {
+        let filter = GcsFilter::from_content(vec![]);
+        assert_eq!(filter.match_any(0, 0, &[b"foo"]), Err(Error::UnexpectedEof));
+        // Claims two elements but only encodes one
+        let filter = GcsFilter::from_content(Vec::from_hex("029dfca8").unwrap());
+        assert_eq!(filter.match_any(0, 0, &[b"foo"]), Err(Error::UnexpectedEof));
+        let filter = GcsFilter::from_content(Vec::from_hex("fd0100").unwrap());
+        assert_eq!(filter.n_elements(), Err(Error::NonMinimalVarInt));
+        let filter = GcsFilter::from_content(Vec::from_hex("fdfd00").unwrap());
+        assert_eq!(filter.n_elements(), Err(Error::UnexpectedEof));
+    }
+}
@@ -35,6 +35,17 @@ crate::internal_macros::hash_type! {
 
 type HashEngine = sha256::HashEngine;
 
+impl Hash {
+    /// Hashes `bytes` in a const context.
+    ///
+    /// This is much slower than [`Hash::hash`](crate::Hash::hash), which should be used at
+    /// runtime. Only available on Rust 1.46 and later.
+    #[cfg(rust_v_1_46)]
+    pub const fn const_hash(bytes: &[u8]) -> Hash {
+        Hash(ripemd160::hash_32_const(&sha256::hash_const(bytes)))
+    }
+}
+
 fn from_engine(e: HashEngine) -> Hash {
     use crate::Hash as _;
 
@@ -48,6 +59,22 @@ fn from_engine(e: HashEngine) -> Hash {
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    #[cfg(rust_v_1_46)]
+    fn const_hash() {
+        use crate::{Hash, hash160};
+
+        const HASH: hash160::Hash = hash160::Hash::const_hash(b"abc");
+        assert_eq!(HASH, hash160::Hash::hash(b"abc"));
+
+        // One to four blocks, around the padding boundaries
+        let data = [0xa5; 200];
+        for len in 0..data.len() {
+            let bytes = &data[..len];
+            assert_eq!(hash160::Hash::const_hash(bytes), hash160::Hash::hash(bytes), "length {}", len);
+        }
+    }
+
     #[test]
     #[cfg(any(feature = "std", feature = "alloc"))]
     fn test() {
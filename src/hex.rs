@@ -128,6 +128,34 @@ fn chars_to_hex(hi: u8, lo: u8) -> Result<u8, Error> {
     Ok(ret as u8)
 }
 
+/// Decodes byte `index` of a `len`-byte array from its hex string, in a const context.
+///
+/// The string is reversed if `backward` is set. Used by the `from_hex_const` functions of the
+/// hash types.
+///
+/// # Panics
+///
+/// If `hex` is not `2 * len` hex digits, which fails the build in a const context.
+#[doc(hidden)]
+#[cfg(rust_v_1_61)]
+pub const fn byte_from_hex_const(hex: &str, len: usize, index: usize, backward: bool) -> u8 {
+    const fn digit(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("invalid hex character"),
+        }
+    }
+
+    let hex = hex.as_bytes();
+    if hex.len() != 2 * len {
+        panic!("invalid hex string length");
+    }
+    let pos = if backward { len - 1 - index } else { index };
+    (digit(hex[2 * pos]) << 4) | digit(hex[2 * pos + 1])
+}
+
 impl<'a> Iterator for HexIterator<'a> {
     type Item = Result<u8, Error>;
 
@@ -292,6 +320,7 @@ impl_fromhex_array!(24);
 impl_fromhex_array!(28);
 impl_fromhex_array!(32);
 impl_fromhex_array!(33);
+impl_fromhex_array!(48);
 impl_fromhex_array!(64);
 impl_fromhex_array!(65);
 impl_fromhex_array!(128);
@@ -0,0 +1,205 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! HMAC-based Extract-and-Expand Key Derivation Function (HKDF).
+//!
+//! Implementation of RFC 5869, generic over the underlying hash function. Output is written to
+//! caller-provided buffers so no allocation is needed.
+//!
+
+use core::fmt;
+
+use crate::{Hash, HashEngine};
+use crate::hmac::{Hmac, HmacEngine};
+
+/// Maximum number of blocks of the output of [`Hkdf::expand`].
+const MAX_OUTPUT_BLOCKS: usize = 255;
+
+/// Error returned when more output is requested than HKDF can produce.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaxLengthError {
+    max: usize,
+}
+
+impl MaxLengthError {
+    /// Returns the maximum output length of the hash function, in bytes.
+    pub fn max_len(&self) -> usize {
+        self.max
+    }
+}
+
+impl fmt::Display for MaxLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HKDF output is limited to {} bytes", self.max)
+    }
+}
+
+/// HKDF pseudorandom key, from which output keying material is expanded.
+#[derive(Copy, Clone)]
+pub struct Hkdf<T: Hash> {
+    prk: Hmac<T>,
+}
+
+impl<T: Hash> Hkdf<T> {
+    /// Extracts a pseudorandom key from the input keying material `ikm`.
+    ///
+    /// An empty `salt` is equivalent to a string of `T::LEN` zeroes.
+    pub fn extract(salt: &[u8], ikm: &[u8]) -> Hkdf<T> {
+        let mut engine = HmacEngine::<T>::new(salt);
+        engine.input(ikm);
+        Hkdf { prk: Hmac::from_engine(engine) }
+    }
+
+    /// Creates an instance from an existing pseudorandom key, skipping the extract step.
+    pub fn from_prk(prk: Hmac<T>) -> Hkdf<T> {
+        Hkdf { prk }
+    }
+
+    /// Returns the pseudorandom key.
+    pub fn prk(&self) -> Hmac<T> {
+        self.prk
+    }
+
+    /// Fills `okm` with output keying material bound to the context `info`.
+    ///
+    /// # Errors
+    ///
+    /// If `okm` is longer than 255 times the output length of the hash function.
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<(), MaxLengthError> {
+        let max = MAX_OUTPUT_BLOCKS * T::LEN;
+        if okm.len() > max {
+            return Err(MaxLengthError { max });
+        }
+
+        let keyed = HmacEngine::<T>::new(&self.prk[..]);
+        let mut previous: Option<Hmac<T>> = None;
+        for (counter, chunk) in okm.chunks_mut(T::LEN).enumerate() {
+            let mut engine = keyed.clone();
+            if let Some(previous) = previous {
+                engine.input(&previous[..]);
+            }
+            engine.input(info);
+            engine.input(&[counter as u8 + 1]);
+
+            let block = Hmac::from_engine(engine);
+            chunk.copy_from_slice(&block[..chunk.len()]);
+            previous = Some(block);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sha256, sha512};
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::sha1;
+        use crate::hex::{FromHex, ToHex};
+
+        struct Test {
+            salt: &'static str,
+            ikm: &'static str,
+            info: &'static str,
+            prk: &'static str,
+            okm: &'static str,
+        }
+
+        // Test vectors from RFC 5869
+        let sha256_tests = [
+            Test {
+                salt: "000102030405060708090a0b0c",
+                ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                info: "f0f1f2f3f4f5f6f7f8f9",
+                prk: "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+                okm: "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+            },
+            Test {
+                salt: "606162636465666768696a6b6c6d6e6f707172737475767778797a7b7c7d7e7f\
+                       808182838485868788898a8b8c8d8e8f909192939495969798999a9b9c9d9e9f\
+                       a0a1a2a3a4a5a6a7a8a9aaabacadaeaf",
+                ikm: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f\
+                      202122232425262728292a2b2c2d2e2f303132333435363738393a3b3c3d3e3f\
+                      404142434445464748494a4b4c4d4e4f",
+                info: "b0b1b2b3b4b5b6b7b8b9babbbcbdbebfc0c1c2c3c4c5c6c7c8c9cacbcccdcecf\
+                       d0d1d2d3d4d5d6d7d8d9dadbdcdddedfe0e1e2e3e4e5e6e7e8e9eaebecedeeef\
+                       f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+                prk: "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+                okm: "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c\
+                      59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71\
+                      cc30c58179ec3e87c14c01d5c1f3434f1d87",
+            },
+            Test {
+                salt: "",
+                ikm: "0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b",
+                info: "",
+                prk: "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+                okm: "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+            },
+        ];
+        let sha1_tests = [
+            Test {
+                salt: "000102030405060708090a0b0c",
+                ikm: "0b0b0b0b0b0b0b0b0b0b0b",
+                info: "f0f1f2f3f4f5f6f7f8f9",
+                prk: "9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243",
+                okm: "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896",
+            },
+            Test {
+                salt: "",
+                ikm: "0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c0c",
+                info: "",
+                prk: "2adccada18779e7c2077ad2eb19d3f3e731385dd",
+                okm: "2c91117204d745f3500d636a62f64f0ab3bae548aa53d423b0d1f27ebba6f5e5673a081d70cce7acfc48",
+            },
+        ];
+
+        for test in sha256_tests.iter() {
+            let hkdf = Hkdf::<sha256::Hash>::extract(&Vec::from_hex(test.salt).unwrap(), &Vec::from_hex(test.ikm).unwrap());
+            assert_eq!(hkdf.prk().to_hex(), test.prk);
+            let mut okm = vec![0; test.okm.len() / 2];
+            hkdf.expand(&Vec::from_hex(test.info).unwrap(), &mut okm).unwrap();
+            assert_eq!(okm.to_hex(), test.okm);
+        }
+        for test in sha1_tests.iter() {
+            let hkdf = Hkdf::<sha1::Hash>::extract(&Vec::from_hex(test.salt).unwrap(), &Vec::from_hex(test.ikm).unwrap());
+            assert_eq!(hkdf.prk().to_hex(), test.prk);
+            let mut okm = vec![0; test.okm.len() / 2];
+            hkdf.expand(&Vec::from_hex(test.info).unwrap(), &mut okm).unwrap();
+            assert_eq!(okm.to_hex(), test.okm);
+        }
+    }
+
+    #[test]
+    fn output_length() {
+        let hkdf = Hkdf::<sha256::Hash>::extract(b"salt", b"ikm");
+
+        // Shorter outputs are prefixes of longer ones
+        let mut long = [0; 255 * 32];
+        hkdf.expand(b"info", &mut long).unwrap();
+        let mut short = [0; 33];
+        hkdf.expand(b"info", &mut short).unwrap();
+        assert_eq!(short[..], long[..33]);
+        hkdf.expand(b"info", &mut []).unwrap();
+
+        let mut too_long = [0; 255 * 32 + 1];
+        assert_eq!(hkdf.expand(b"info", &mut too_long).unwrap_err().max_len(), 255 * 32);
+        let mut too_long = [0; 255 * 64 + 1];
+        let hkdf = Hkdf::<sha512::Hash>::from_prk(Hmac::hash(b"prk"));
+        assert_eq!(hkdf.expand(b"info", &mut too_long).unwrap_err().max_len(), 255 * 64);
+    }
+}
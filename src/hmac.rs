@@ -24,7 +24,10 @@ use core::{borrow, fmt, ops, str};
 #[cfg(feature = "serde")]
 use serde::{Serialize, Serializer, Deserialize, Deserializer};
 
-use crate::{Error, Hash, HashEngine};
+#[cfg(feature = "serde")]
+use crate::serde_macros::serde_details::MidStateBytes;
+
+use crate::{Error, FromMidState, Hash, HashEngine};
 
 /// A hash computed from a RFC 2104 HMAC. Parameterized by the underlying hash function.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -41,6 +44,9 @@ impl<T: Hash + str::FromStr> str::FromStr for Hmac<T> {
 }
 
 /// Pair of underlying hash midstates which represent the current state of an `HmacEngine`.
+///
+/// With the `serde` feature, both midstates are serialized as their bytes, in hex for
+/// human-readable formats.
 pub struct HmacMidState<T: Hash> {
     /// Midstate of the inner hash engine
     pub inner: <T::Engine as HashEngine>::MidState,
@@ -48,6 +54,24 @@ pub struct HmacMidState<T: Hash> {
     pub outer: <T::Engine as HashEngine>::MidState,
 }
 
+impl<T: Hash> Clone for HmacMidState<T> where <T::Engine as HashEngine>::MidState: Clone {
+    fn clone(&self) -> Self {
+        HmacMidState {
+            inner: self.inner.clone(),
+            outer: self.outer.clone(),
+        }
+    }
+}
+
+impl<T: Hash> fmt::Debug for HmacMidState<T> where <T::Engine as HashEngine>::MidState: fmt::Debug {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HmacMidState")
+            .field("inner", &self.inner)
+            .field("outer", &self.outer)
+            .finish()
+    }
+}
+
 /// Pair of underyling hash engines, used for the inner and outer hash of HMAC.
 #[derive(Clone)]
 pub struct HmacEngine<T: Hash> {
@@ -64,16 +88,17 @@ impl<T: Hash> Default for HmacEngine<T> {
 impl<T: Hash> HmacEngine<T> {
     /// Constructs a new keyed HMAC from `key`.
     ///
-    /// We only support underlying hashes whose block sizes are ≤ 128 bytes.
+    /// We only support underlying hashes whose block sizes are ≤ 168 bytes, which covers the
+    /// rates of all the Keccak-based hashes.
     ///
     /// # Panics
     ///
     /// Larger hashes will result in a panic.
     pub fn new(key: &[u8]) -> HmacEngine<T> {
-        debug_assert!(T::Engine::BLOCK_SIZE <= 128);
+        debug_assert!(T::Engine::BLOCK_SIZE <= 168);
 
-        let mut ipad = [0x36u8; 128];
-        let mut opad = [0x5cu8; 128];
+        let mut ipad = [0x36u8; 168];
+        let mut opad = [0x5cu8; 168];
         let mut ret = HmacEngine {
             iengine: <T as Hash>::engine(),
             oengine: <T as Hash>::engine(),
@@ -101,6 +126,25 @@ impl<T: Hash> HmacEngine<T> {
         ret
     }
 
+    /// Constructs an engine from the midstate of a keyed engine, e.g. to skip hashing the padded
+    /// key again when the same key is used many times.
+    ///
+    /// `length` is the number of bytes hashed by the inner engine, which is the block size for a
+    /// midstate taken right after keying.
+    ///
+    /// # Panics
+    ///
+    /// If `length` is not a multiple of the block size.
+    pub fn from_midstate(midstate: HmacMidState<T>, length: usize) -> HmacEngine<T>
+    where
+        T::Engine: FromMidState,
+    {
+        HmacEngine {
+            iengine: T::Engine::from_midstate(midstate.inner, length),
+            oengine: T::Engine::from_midstate(midstate.outer, T::Engine::BLOCK_SIZE),
+        }
+    }
+
     /// A special constructor giving direct access to the underlying "inner" and "outer" engines.
     pub fn from_inner_engines(iengine: T::Engine, oengine: T::Engine) -> HmacEngine<T> {
         HmacEngine {
@@ -131,6 +175,12 @@ impl<T: Hash> HashEngine for HmacEngine<T> {
     }
 }
 
+impl<T: Hash> FromMidState for HmacEngine<T> where T::Engine: FromMidState {
+    fn from_midstate(midstate: HmacMidState<T>, length: usize) -> HmacEngine<T> {
+        HmacEngine::from_midstate(midstate, length)
+    }
+}
+
 impl<T: Hash> fmt::Debug for Hmac<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Debug::fmt(&self.0, f)
@@ -242,6 +292,84 @@ impl<'de, T: Hash + Deserialize<'de>> Deserialize<'de> for Hmac<T> {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<T: Hash> Serialize for HmacMidState<T> where <T::Engine as HashEngine>::MidState: MidStateBytes {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(&(SerializeMidState(&self.inner), SerializeMidState(&self.outer)), s)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, T: Hash> Deserialize<'de> for HmacMidState<T> where <T::Engine as HashEngine>::MidState: MidStateBytes {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<HmacMidState<T>, D::Error> {
+        let (DeserializeMidState(inner), DeserializeMidState(outer)) = Deserialize::deserialize(d)?;
+        Ok(HmacMidState { inner, outer })
+    }
+}
+
+/// Serializes a midstate as hex for human-readable formats, and as a byte string otherwise.
+#[cfg(feature = "serde")]
+struct SerializeMidState<'a, M>(&'a M);
+
+#[cfg(feature = "serde")]
+impl<'a, M: MidStateBytes> fmt::Display for SerializeMidState<'a, M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        crate::hex::format_hex(self.0.midstate_bytes(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, M: MidStateBytes> Serialize for SerializeMidState<'a, M> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        if s.is_human_readable() {
+            s.collect_str(self)
+        } else {
+            s.serialize_bytes(self.0.midstate_bytes())
+        }
+    }
+}
+
+/// Deserializes a midstate serialized by [`SerializeMidState`].
+#[cfg(feature = "serde")]
+struct DeserializeMidState<M>(M);
+
+#[cfg(feature = "serde")]
+impl<'de, M: MidStateBytes> Deserialize<'de> for DeserializeMidState<M> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<DeserializeMidState<M>, D::Error> {
+        use core::marker::PhantomData;
+        use serde::de;
+
+        struct Visitor<M>(PhantomData<M>);
+
+        impl<'de, M: MidStateBytes> de::Visitor<'de> for Visitor<M> {
+            type Value = M;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a hash engine midstate")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<M, E> {
+                let iter = crate::hex::HexIterator::new(v).map_err(E::custom)?;
+                M::from_byte_iter(iter).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<M, E> {
+                M::from_byte_iter(v.iter().map(|byte| Ok(*byte)))
+                    .map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        let midstate = if d.is_human_readable() {
+            d.deserialize_str(Visitor(PhantomData))?
+        } else {
+            d.deserialize_bytes(Visitor(PhantomData))?
+        };
+        Ok(DeserializeMidState(midstate))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -369,6 +497,76 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn hmac_sha384() {
+        use crate::{sha384, HashEngine, HmacEngine, Hash, Hmac};
+        use crate::hex::ToHex;
+
+        // Test vectors from RFC 4231
+        let tests: [(&[u8], &[u8], &str); 3] = [
+            (
+                &[0x0b; 20],
+                b"Hi There",
+                "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec6\
+                 82aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47\
+                 e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+            ),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f\
+                 3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952",
+            ),
+        ];
+
+        for (key, input, output) in tests.iter() {
+            let mut engine = HmacEngine::<sha384::Hash>::new(key);
+            engine.input(input);
+            let hash = Hmac::<sha384::Hash>::from_engine(engine);
+            assert_eq!(&hash.to_hex(), output);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn hmac_sha3() {
+        use crate::{sha3, HashEngine, HmacEngine, Hash, Hmac};
+        use crate::hex::ToHex;
+
+        // Test vectors computed with Python's `hmac` module. The 200-byte key is longer than both
+        // the 136-byte block of SHA3-256 and the 72-byte block of SHA3-512, so it is hashed first.
+        let key = [0xaa; 200];
+        let input = b"Test Using Larger Than Block-Size Key - Hash Key First";
+
+        let mut engine = HmacEngine::<sha3::sha3_256::Hash>::new(b"Jefe");
+        engine.input(b"what do ya want for nothing?");
+        assert_eq!(
+            Hmac::<sha3::sha3_256::Hash>::from_engine(engine).to_hex(),
+            "c7d4072e788877ae3596bbb0da73b887c9171f93095b294ae857fbe2645e1ba5",
+        );
+
+        let mut engine = HmacEngine::<sha3::sha3_256::Hash>::new(&key);
+        engine.input(input);
+        assert_eq!(
+            Hmac::<sha3::sha3_256::Hash>::from_engine(engine).to_hex(),
+            "49ad92b02124fdac9627ae45e008a696182ab6bfb8470457777c744aeb9df06f",
+        );
+
+        let mut engine = HmacEngine::<sha3::sha3_512::Hash>::new(&key);
+        engine.input(input);
+        assert_eq!(
+            Hmac::<sha3::sha3_512::Hash>::from_engine(engine).to_hex(),
+            "fafc7b7fe3332ce153966b27f6586fa5b49ec5d8dff3d7fd26a011451ca4c9de\
+             437913879159d9c5181a9a6f377ef18b48399756decea695b04fe90a9d3b93d1",
+        );
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn hmac_sha512_serde() {
@@ -396,6 +594,111 @@ mod tests {
             )],
         );
     }
+
+    #[test]
+    fn hmac_from_midstate() {
+        use crate::{sha224, sha256, sha256d, sha384, sha512, sha512_224, sha512_256};
+        use crate::{Hash, HashEngine, HmacEngine, Hmac};
+        use crate::hex::ToHex;
+
+        fn check<T: Hash>() where T::Engine: crate::FromMidState {
+            let keyed = HmacEngine::<T>::new(b"chain code");
+            let resumed = HmacEngine::<T>::from_midstate(keyed.midstate(), T::Engine::BLOCK_SIZE);
+            for data in [&b""[..], b"child", &[0xab; 200]].iter() {
+                let mut expected = keyed.clone();
+                expected.input(data);
+                let mut engine = resumed.clone();
+                engine.input(data);
+                assert_eq!(Hmac::from_engine(engine), Hmac::<T>::from_engine(expected));
+            }
+
+            // Resuming after some full blocks of input
+            let mut engine = keyed.clone();
+            engine.input(&[1; 128]);
+            let mut resumed = HmacEngine::<T>::from_midstate(engine.midstate(), engine.n_bytes_hashed());
+            engine.input(b"tail");
+            resumed.input(b"tail");
+            assert_eq!(Hmac::from_engine(resumed), Hmac::<T>::from_engine(engine));
+        }
+
+        check::<sha256::Hash>();
+        check::<sha256d::Hash>();
+        check::<sha224::Hash>();
+        check::<sha512::Hash>();
+        check::<sha384::Hash>();
+        check::<sha512_256::Hash>();
+        check::<sha512_224::Hash>();
+
+        // RFC 4231 test case 2, resumed from the midstate of the keyed engine
+        let keyed = HmacEngine::<sha512::Hash>::new(b"Jefe");
+        let mut engine = HmacEngine::<sha512::Hash>::from_midstate(keyed.midstate(), 128);
+        engine.input(b"what do ya want for nothing?");
+        assert_eq!(
+            Hmac::from_engine(engine).to_hex(),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn hmac_from_midstate_partial_block() {
+        use crate::{sha256, HashEngine, HmacEngine};
+
+        let engine = HmacEngine::<sha256::Hash>::new(b"key");
+        HmacEngine::<sha256::Hash>::from_midstate(engine.midstate(), 65);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn hmac_midstate_serde() {
+        use crate::{sha256, HashEngine, HmacEngine};
+        use super::HmacMidState;
+
+        let midstate = HmacEngine::<sha256::Hash>::new(b"key").midstate();
+        let json = serde_json::to_string(&midstate).unwrap();
+        assert_eq!(
+            json,
+            "[\"ce6cc13a481ee179741ff12a377e06dda6103e8964a893646c03368b82dceb35\",\
+              \"3dec8b3e7eabbbec7fe6dd4d1727b9c916cd5cbece6cd05b590d78dca9c44562\"]",
+        );
+        let decoded: HmacMidState<sha256::Hash> = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", midstate.clone()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn hmac_sha512_midstate_serde() {
+        use serde_test::{Configure, Token, assert_ser_tokens};
+        use crate::{sha512, Hash, HashEngine, HmacEngine, Hmac};
+        use crate::hex::ToHex;
+        use super::HmacMidState;
+
+        let midstate = HmacEngine::<sha512::Hash>::new(b"Jefe").midstate();
+        let json = serde_json::to_string(&midstate).unwrap();
+        let decoded: HmacMidState<sha512::Hash> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.inner[..], midstate.inner[..]);
+        assert_eq!(decoded.outer[..], midstate.outer[..]);
+
+        // RFC 4231 test case 2, resumed from the deserialized midstate
+        let mut engine = HmacEngine::<sha512::Hash>::from_midstate(decoded, 128);
+        engine.input(b"what do ya want for nothing?");
+        assert_eq!(
+            Hmac::from_engine(engine).to_hex(),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+
+        // Tokens need static byte strings
+        let inner: &'static [u8] = Box::leak(midstate.inner.to_vec().into_boxed_slice());
+        let outer: &'static [u8] = Box::leak(midstate.outer.to_vec().into_boxed_slice());
+        assert_ser_tokens(&(&midstate).compact(), &[
+            Token::Tuple { len: 2 },
+            Token::Bytes(inner),
+            Token::Bytes(outer),
+            Token::TupleEnd,
+        ]);
+    }
 }
 
 #[cfg(bench)]
@@ -0,0 +1,183 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! HMAC-based Deterministic Random Bit Generator (HMAC-DRBG).
+//!
+//! Implementation of the HMAC-DRBG of NIST SP 800-90A, generic over the underlying hash function.
+//! The reseed counter is not tracked: callers which need prediction resistance have to reseed
+//! on their own schedule.
+//!
+
+use crate::{Hash, HashEngine};
+use crate::hmac::{Hmac, HmacEngine};
+
+/// State of an HMAC-DRBG.
+#[derive(Clone)]
+pub struct HmacDrbg<T: Hash> {
+    k: Hmac<T>,
+    v: Hmac<T>,
+}
+
+impl<T: Hash> HmacDrbg<T> {
+    /// Instantiates a generator from `entropy`, a `nonce` and an optional `personalization`
+    /// string.
+    ///
+    /// # Panics
+    ///
+    /// If the output of `T` is longer than 64 bytes.
+    pub fn new(entropy: &[u8], nonce: &[u8], personalization: &[u8]) -> HmacDrbg<T> {
+        HmacDrbg::from_seed(&[entropy, nonce, personalization])
+    }
+
+    /// Instantiates a generator from the concatenation of `seed`.
+    pub(crate) fn from_seed(seed: &[&[u8]]) -> HmacDrbg<T> {
+        assert!(T::LEN <= 64, "HMAC-DRBG supports hash outputs of at most 64 bytes");
+        let mut drbg = HmacDrbg {
+            k: Hmac::all_zeros(),
+            v: Hmac::from_slice(&[0x01; 64][..T::LEN]).expect("output length checked"),
+        };
+        drbg.update(seed);
+        drbg
+    }
+
+    /// Mixes fresh `entropy` and optional `additional` input into the state.
+    pub fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        self.update(&[entropy, additional]);
+    }
+
+    /// Fills `out` with pseudorandom bytes, after mixing optional `additional` input into the
+    /// state.
+    pub fn generate(&mut self, out: &mut [u8], additional: &[u8]) {
+        if !additional.is_empty() {
+            self.update(&[additional]);
+        }
+        for chunk in out.chunks_mut(T::LEN) {
+            self.v = self.hmac(None, &[]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[additional]);
+    }
+
+    /// Computes `HMAC(K, V || separator || data)`, omitting the separator if it is `None`.
+    fn hmac(&self, separator: Option<u8>, data: &[&[u8]]) -> Hmac<T> {
+        let mut engine = HmacEngine::<T>::new(&self.k[..]);
+        engine.input(&self.v[..]);
+        if let Some(separator) = separator {
+            engine.input(&[separator]);
+        }
+        for slice in data {
+            engine.input(slice);
+        }
+        Hmac::from_engine(engine)
+    }
+
+    /// The `HMAC_DRBG_Update` function, with the concatenation of `data` as provided data.
+    fn update(&mut self, data: &[&[u8]]) {
+        self.k = self.hmac(Some(0x00), data);
+        self.v = self.hmac(None, &[]);
+
+        if data.iter().any(|slice| !slice.is_empty()) {
+            self.k = self.hmac(Some(0x01), data);
+            self.v = self.hmac(None, &[]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn drbg() {
+        use super::HmacDrbg;
+        use crate::{sha256, sha512};
+        use crate::hex::{FromHex, ToHex};
+
+        struct Test {
+            entropy: &'static str,
+            nonce: &'static str,
+            additional: [&'static str; 2],
+            output: &'static str,
+        }
+
+        // NIST CAVP vectors for HMAC_DRBG with SHA-256, without prediction resistance or reseeding
+        // and with an empty personalization string. The output is that of the second of two
+        // generate calls.
+        let tests = [
+            Test {
+                entropy: "ca851911349384bffe89de1cbdc46e6831e44d34a4fb935ee285dd14b71a7488",
+                nonce: "659ba96c601dc69fc902940805ec0ca8",
+                additional: ["", ""],
+                output: "e528e9abf2dece54d47c7e75e5fe302149f817ea9fb4bee6f4199697d04d5b89\
+                 d54fbb978a15b5c443c9ec21036d2460b6f73ebad0dc2aba6e624abf07745bc1\
+                 07694bb7547bb0995f70de25d6b29e2d3011bb19d27676c07162c8b5ccde0668\
+                 961df86803482cb37ed6d5c0bb8d50cf1f50d476aa0458bdaba806f48be9dcb8",
+            },
+            Test {
+                entropy: "79737479ba4e7642a221fcfd1b820b134e9e3540a35bb48ffae29c20f5418ea3",
+                nonce: "3593259c092bef4129bc2c6c9e19f343",
+                additional: ["", ""],
+                output: "cf5ad5984f9e43917aa9087380dac46e410ddc8a7731859c84e9d0f31bd43655\
+                 b924159413e2293b17610f211e09f770f172b8fb693a35b85d3b9e5e63b1dc25\
+                 2ac0e115002e9bedfb4b5b6fd43f33b8e0eafb2d072e1a6fee1f159df9b51e6c\
+                 8da737e60d5032dd30544ec51558c6f080bdbdab1de8a939e961e06b5f1aca37",
+            },
+            Test {
+                entropy: "d3cc4d1acf3dde0c4bd2290d262337042dc632948223d3a2eaab87da44295fbd",
+                nonce: "0109b0e729f457328aa18569a9224921",
+                additional: ["3c311848183c9a212a26f27f8c6647e40375e466a0857cc39c4e47575d53f1f6", "fcb9abd19ccfbccef88c9c39bfb3dd7b1c12266c9808992e305bc3cff566e4e4"],
+                output: "9c7b758b212cd0fcecd5daa489821712e3cdea4467b560ef5ddc24ab47749a1f\
+                 1ffdbbb118f4e62fcfca3371b8fbfc5b0646b83e06bfbbab5fac30ea09ea2bc7\
+                 6f1ea568c9be0444b2cc90517b20ca825f2d0eccd88e7175538b85d90ab39018\
+                 3ca6395535d34473af6b5a5b88f5a59ee7561573337ea819da0dcc3573a22974",
+            },
+            Test {
+                entropy: "f97a3cfd91faa046b9e61b9493d436c4931f604b22f1081521b3419151e8ff06",
+                nonce: "11f3a7d43595357d58120bd1e2dd8aed",
+                additional: ["517289afe444a0fe5ed1a41dbbb5eb17150079bdd31e29cf2ff30034d8268e3b", "88028d29ef80b4e6f0fe12f91d7449fe75062682e89c571440c0c9b52c42a6e0"],
+                output: "c6871cff0824fe55ea7689a52229886730450e5d362da5bf590dcf9acd67fed4\
+                 cb32107df5d03969a66b1f6494fdf5d63d5b4d0d34ea7399a07d0116126d0d51\
+                 8c7c55ba46e12f62efc8fe28a51c9d428e6d371d7397ab319fc73ded4722e5b4\
+                 f30004032a6128df5e7497ecf82ca7b0a50e867ef6728a4f509a8c859087039c",
+            },
+        ];
+        for test in tests.iter() {
+            let entropy = Vec::from_hex(test.entropy).unwrap();
+            let nonce = Vec::from_hex(test.nonce).unwrap();
+            let mut drbg = HmacDrbg::<sha256::Hash>::new(&entropy, &nonce, &[]);
+            let mut out = [0; 128];
+            drbg.generate(&mut out, &Vec::from_hex(test.additional[0]).unwrap());
+            drbg.generate(&mut out, &Vec::from_hex(test.additional[1]).unwrap());
+            assert_eq!(out.to_hex(), test.output);
+        }
+
+        // Reseeding changes the output
+        let mut drbg = HmacDrbg::<sha256::Hash>::new(b"entropy", b"nonce", b"personalization");
+        let mut reseeded = drbg.clone();
+        reseeded.reseed(b"more entropy", &[]);
+        let mut out = [0; 32];
+        drbg.generate(&mut out, &[]);
+        let mut reseeded_out = [0; 32];
+        reseeded.generate(&mut reseeded_out, &[]);
+        assert_ne!(out, reseeded_out);
+
+        // A shorter request returns a prefix of a longer one
+        let mut drbg = HmacDrbg::<sha512::Hash>::new(b"entropy", &[], &[]);
+        let mut clone = drbg.clone();
+        let mut long = [0; 130];
+        drbg.generate(&mut long, &[]);
+        let mut short = [0; 65];
+        clone.generate(&mut short, &[]);
+        assert_eq!(long[..65], short[..]);
+    }
+}
@@ -23,7 +23,7 @@ use std::{error, io};
 #[cfg(not(feature = "std"))]
 use core2::{error, io};
 
-use crate::{Error, HashEngine, hex, sha1, sha256, sha512, ripemd160, siphash24, hmac};
+use crate::{Error, HashEngine, hex, blake2, blake3, sha1, sha224, sha256, sha384, sha512, sha512_256, sha512_224, sha3, ripemd160, murmur3, siphash24, hmac};
 
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
@@ -45,6 +45,27 @@ impl error::Error for hex::Error {
     }
 }
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl error::Error for crate::gcs::Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        use crate::gcs::Error::*;
+
+        match *self {
+            UnexpectedEof | NonMinimalVarInt => None
+        }
+    }
+}
+
+impl error::Error for crate::hkdf::MaxLengthError {}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl error::Error for crate::merkleblock::Error {}
+
+impl error::Error for crate::scrypt::InvalidParamsError {}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl error::Error for crate::taproot::Error {}
+
 impl<'a> io::Read for hex::HexIterator<'a> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut bytes_read = 0usize;
@@ -70,6 +91,15 @@ impl io::Write for sha1::HashEngine {
     }
 }
 
+impl io::Write for sha224::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
 impl io::Write for sha256::HashEngine {
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 
@@ -79,6 +109,15 @@ impl io::Write for sha256::HashEngine {
     }
 }
 
+impl io::Write for sha384::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
 impl io::Write for sha512::HashEngine {
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 
@@ -88,6 +127,137 @@ impl io::Write for sha512::HashEngine {
     }
 }
 
+impl io::Write for sha512_256::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for sha512_224::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for blake2::Blake2b {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for blake2::Blake2s {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for blake2::blake2b_256::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for blake2::blake2b_512::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for blake2::blake2s_256::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for blake3::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Read for blake3::OutputReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.squeeze(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for sha3::sha3_256::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for sha3::sha3_512::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for sha3::keccak256::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for sha3::Shake128 {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Write for sha3::Shake256 {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl io::Read for sha3::ShakeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.squeeze(buf);
+        Ok(buf.len())
+    }
+}
+
 impl io::Write for ripemd160::HashEngine {
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 
@@ -97,7 +267,16 @@ impl io::Write for ripemd160::HashEngine {
     }
 }
 
-impl io::Write for siphash24::HashEngine {
+impl io::Write for murmur3::HashEngine {
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.input(buf);
+        Ok(buf.len())
+    }
+}
+
+impl<V: siphash24::Variant> io::Write for siphash24::SipHashEngine<V> {
     fn flush(&mut self) -> io::Result<()> { Ok(()) }
 
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
@@ -119,7 +298,9 @@ impl<T: crate::Hash> io::Write for hmac::HmacEngine<T> {
 mod tests {
     use super::io::Write;
 
-    use crate::{Hash, sha1, sha256, sha256d, sha512, ripemd160, hash160, siphash24, hmac};
+    use crate::{Hash, blake3, sha1, sha224, sha256, sha256d, sha384, sha512, sha512_256, sha512_224, ripemd160, hash160, murmur3, siphash13, siphash24, siphash24_128, hmac};
+    use crate::blake2::{blake2b_256, blake2b_512, blake2s_256};
+    use crate::sha3::{sha3_256, sha3_512, keccak256};
 
     macro_rules! write_test {
         ($mod:ident, $exp_empty:expr, $exp_256:expr, $exp_64k:expr,) => {
@@ -156,6 +337,13 @@ mod tests {
         "e4b66838f9f7b6f91e5be32a02ae78094df402e7",
     );
 
+    write_test!(
+        sha224,
+        "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+        "4729c84e59381c0cc83d7171caf635a2ccffe4adbfd6db9e0e32999e",
+        "a72e39979b827f94653d6f7da918cc3ffc3ae6ba271fa50f706b5bdf",
+    );
+
     write_test!(
         sha256,
         "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
@@ -180,6 +368,82 @@ mod tests {
          4761df8d04ed04bb734ba48dd2106bb9ea54524f1394cdd18e6da3166e71c3ee",
     );
 
+    write_test!(
+        sha384,
+        "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b",
+        "82135637ef6d6dd31a20e2bc9998681a3eecaf8f8c76d45e545214de38439d9a533848ec75f53e4b1a8805709c5124d0",
+        "fb7511d9a98c5686f9c2f55e242397815c9229d8759451e1710b8da6861e08d52f0357176f4b74f8cad9e23ab65411c7",
+    );
+
+    write_test!(
+        sha512_256,
+        "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a",
+        "8d4bb96e7956cf5f08bf5c45f7982630c46b0b022f25cbaf722ae97c06a6e7a2",
+        "3367646f3e264653f7dd664ac2cb6d3b96329e86ffb7a29a1082e2a4ddc9ee7a",
+    );
+
+    write_test!(
+        sha512_224,
+        "6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4",
+        "17586d9ed4e20a8c54451f55c37012ab80a0455eb88384bb5f0dd8ef",
+        "2de99b4a6b847edac554cddef6ae73c11280b2099185b44f6f0299ee",
+    );
+
+    write_test!(
+        sha3_256,
+        "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+        "8c7a8eec38a0c96723672927b9f4d0f7b5c5f334ee94debda979fb3696ab7581",
+        "60af0ef4115a72e570b1a414c7b8dc6c83677adf1ce2d268d28becd8e0fdd1e6",
+    );
+
+    write_test!(
+        sha3_512,
+        "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6\
+         15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+        "6361f0fd60b3bcd7d80374b9724a6ee010b4ae57b56eaafe3d4321e000e0d873\
+         c53f9142b1a1d3b88b7ebc71d15d7fc589c22cea583558889e7a79ad2ab516c4",
+        "d9430215a681cb5d17125d8ce75814d68d7e9ca87627caebccd96e4b7e6db8b7\
+         c708fb6a32cda02542192fefefed46033190243bafc66f1dd9b13677bdcfda62",
+    );
+
+    write_test!(
+        keccak256,
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+        "10c18b0ffc7f89e6da317ea664365820b9f7f954fa9f03733072f5ccd0826818",
+        "751a3fd6048cc3f5921ba66b8650fb452269d4741e5487c6736ae64b506538be",
+    );
+
+    write_test!(
+        blake2b_256,
+        "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8",
+        "feb016c28375e7a92909827ab94ef89d0e8c8bd6d989054d4d5f0c2048c6ec47",
+        "04377ffc3310d8fa07fcbcb9c51c359d52645aa827e5749cd1d32598c7cf1eb2",
+    );
+
+    write_test!(
+        blake2b_512,
+        "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+         d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+        "dd3335ca787cb9cbf72b59c1461029caadc86dc68373f7852ec9045df3011559\
+         d0629cf1a8262732ae1cfc7c2348767d426cfbb87e1f6a75ff7b00245c3f189d",
+        "1cf41a5d760fc92c686f1926441e1f978add35fb12b60b709125450cb58232a5\
+         7a3dbe51efbb554af94e9b67d0c379168ce84ad95d00d1da2e38568737d3c2cb",
+    );
+
+    write_test!(
+        blake2s_256,
+        "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+        "269b73e8522c4bbbfe3136fdc836d8693fb07a035a2e4e99f67e18ff8e300473",
+        "e6e998731f7db23358149078d9372bdde65985f477d9cdca1cb374c2aa2e4efb",
+    );
+
+    write_test!(
+        blake3,
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+        "33329ca0326344ec01f69de9f4fddf7e2d66807c6b314b8351ad8abe52a3df02",
+        "1bf22709d3a11a0264eb554849197bb2f350bd2843b546707ed40e19def3ccee",
+    );
+
     write_test!(
         ripemd160,
         "9c1185a5c5e9fc54612808977ee8f548b2258d31",
@@ -194,6 +458,20 @@ mod tests {
         "a9608c952c8dbcc20c53803d2ca5ad31d64d9313",
     );
 
+    write_test!(
+        murmur3,
+        "00000000",
+        "68b41dfb",
+        "e720e80a",
+    );
+
+    write_test!(
+        siphash13,
+        "2c530c1562a7fbd1",
+        "4b33ef0702778451",
+        "3dff8110eb8b84fd",
+    );
+
     write_test!(
         siphash24,
         "d70077739d4b921e",
@@ -201,6 +479,13 @@ mod tests {
         "ce456e4e4ecbc5bf",
     );
 
+    write_test!(
+        siphash24_128,
+        "5049d74780a3e07d4202ab47d4cef2f4",
+        "c85a9b21e3e62fc1dcbd6a1e0035fdaf",
+        "32404f713ac13b337ba60ba2c7407341",
+    );
+
     #[test]
     fn hmac() {
         let mut engine = hmac::HmacEngine::<sha256::Hash>::new(&[0xde, 0xad, 0xbe, 0xef]);
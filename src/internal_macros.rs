@@ -13,7 +13,8 @@
 /// Restrictions on usage:
 ///
 /// * There must be a free-standing `fn from_engine(HashEngine) -> Hash` in the scope
-/// * `fn internal_new([u8; $bits / 8]) -> Self` must exist on `Hash`
+/// * `const fn internal_new([u8; $bits / 8]) -> Self` must exist on `Hash`, it may be a plain
+///   `fn` before Rust 1.61 if `Hash` is generic
 /// * `fn internal_engine() -> HashEngine` must exist on `Hash`
 ///
 /// `from_engine` obviously implements the finalization algorithm.
@@ -32,6 +33,36 @@ macro_rules! hash_trait_impls {
         serde_impl!(Hash, $bits / 8 $(, $gen: $gent)*);
         borrow_slice_impl!(Hash $(, $gen: $gent)*);
 
+        impl<$($gen: $gent),*> Hash<$($gen),*> {
+            /// Parses a hash from its hex representation in a const context, e.g. through
+            /// [`hash!`](crate::hash!).
+            ///
+            /// Like [`FromStr`](core::str::FromStr), the string is reversed if the hash is
+            /// displayed backward. Only available on Rust 1.61 and later.
+            ///
+            /// # Panics
+            ///
+            /// If `s` is not `2 * LEN` hex digits, which fails the build in a const context.
+            #[cfg(rust_v_1_61)]
+            pub const fn from_hex_const(s: &str) -> Self {
+                let mut inner = [0; $bits / 8];
+                let mut i = 0;
+                while i < $bits / 8 {
+                    inner[i] = crate::hex::byte_from_hex_const(s, $bits / 8, i, $reversed);
+                    i += 1;
+                }
+                Self::internal_new(inner)
+            }
+
+            /// Constructs a hash from the underlying byte array in a const context.
+            ///
+            /// Only available on Rust 1.61 and later.
+            #[cfg(rust_v_1_61)]
+            pub const fn from_inner_const(inner: [u8; $bits / 8]) -> Self {
+                Self::internal_new(inner)
+            }
+        }
+
         impl<I: SliceIndex<[u8]> $(, $gen: $gent)*> Index<I> for Hash<$($gen),*> {
             type Output = I::Output;
 
@@ -112,7 +143,7 @@ macro_rules! hash_type {
         );
 
         impl Hash {
-            fn internal_new(arr: [u8; $bits / 8]) -> Self {
+            const fn internal_new(arr: [u8; $bits / 8]) -> Self {
                 Hash(arr)
             }
 
@@ -119,17 +119,43 @@ mod internal_macros;
 #[macro_use] mod util;
 #[macro_use] pub mod serde_macros;
 #[cfg(any(feature = "std", feature = "core2"))] mod impls;
+pub mod blake2;
+pub mod blake3;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod bloom;
 pub mod error;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod gcs;
 pub mod hex;
 pub mod hash160;
+pub mod hkdf;
 pub mod hmac;
+pub mod hmac_drbg;
+pub mod merkle;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod merkleblock;
+pub mod muhash;
+pub mod murmur3;
+pub mod pbkdf2;
+pub mod rfc6979;
 pub mod ripemd160;
+pub mod scrypt;
 pub mod sha1;
+pub mod sha224;
 pub mod sha256;
 pub mod sha256d;
-pub mod sha256t;
-pub mod siphash24;
+#[macro_use] pub mod sha256t;
+pub mod sha3;
+pub mod sha384;
 pub mod sha512;
+pub mod sha512_224;
+pub mod sha512_256;
+pub mod shortid;
+pub mod siphash13;
+pub mod siphash24;
+pub mod siphash24_128;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod taproot;
 pub mod cmp;
 
 use core::{borrow, fmt, hash, ops};
@@ -156,6 +182,16 @@ pub trait HashEngine: Clone + Default {
     fn n_bytes_hashed(&self) -> usize;
 }
 
+/// A hashing engine which can be resumed from its midstate.
+pub trait FromMidState: HashEngine {
+    /// Creates an engine from its midstate, after `length` bytes were hashed.
+    ///
+    /// # Panics
+    ///
+    /// If `length` is not a multiple of the block size.
+    fn from_midstate(midstate: Self::MidState, length: usize) -> Self;
+}
+
 /// Trait which applies to hashes of all types.
 pub trait Hash: Copy + Clone + PartialEq + Eq + PartialOrd + Ord +
     hash::Hash + fmt::Debug + fmt::Display + fmt::LowerHex +
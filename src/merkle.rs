@@ -0,0 +1,268 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! Bitcoin merkle trees.
+//!
+//! Computation of the merkle root of a list of hashes, e.g. the txids of a block. Inner nodes
+//! are hashed with the hash type of the leaves, which should be [`sha256d`](crate::sha256d) or
+//! a newtype around it; a level with an odd number of nodes has its last node paired with
+//! itself.
+//!
+//! Because of that rule, a list ending with two identical subtrees has the same root as the
+//! list without the second one (CVE-2012-2459). Like Bitcoin Core's `ComputeMerkleRoot`, both
+//! functions of this module report whether the tree contains identical sibling nodes, in
+//! which case the list of leaves must not be trusted even though the root matches.
+//!
+
+use crate::{Hash, HashEngine};
+
+/// Hashes two sibling nodes into their parent.
+pub(crate) fn parent<T: Hash>(left: &T, right: &T) -> T {
+    let mut engine = T::engine();
+    engine.input(&left[..]);
+    engine.input(&right[..]);
+    T::from_engine(engine)
+}
+
+/// Computes the merkle root of `hashes`, using the slice as scratch space.
+///
+/// Returns the root and whether the tree is mutated, or `None` if `hashes` is empty.
+pub fn compute_root_in_place<T: Hash>(hashes: &mut [T]) -> Option<(T, bool)> {
+    if hashes.is_empty() {
+        return None;
+    }
+
+    let mut mutated = false;
+    let mut len = hashes.len();
+    while len > 1 {
+        for i in 0..(len + 1) / 2 {
+            let left = hashes[2 * i];
+            let right = if 2 * i + 1 < len {
+                mutated |= hashes[2 * i + 1] == left;
+                hashes[2 * i + 1]
+            } else {
+                left
+            };
+            hashes[i] = parent(&left, &right);
+        }
+        len = (len + 1) / 2;
+    }
+    Some((hashes[0], mutated))
+}
+
+/// Computes the merkle root of `hashes` with a [`MerkleBuilder`].
+///
+/// Returns the root and whether the tree is mutated, or `None` if `hashes` is empty.
+pub fn compute_root<T, I>(hashes: I) -> Option<(T, bool)>
+where
+    T: Hash,
+    I: IntoIterator<Item = T>,
+{
+    let mut builder = MerkleBuilder::new();
+    for hash in hashes {
+        builder.push(hash);
+    }
+    builder.finish()
+}
+
+/// Streaming computation of a merkle root, in memory logarithmic in the number of leaves.
+///
+/// Only the left siblings of the path to the next leaf are kept; a full subtree is merged into
+/// its parent as soon as it is complete.
+#[derive(Debug, Clone)]
+pub struct MerkleBuilder<T: Hash> {
+    /// Number of leaves pushed so far.
+    count: u32,
+    /// Roots of the full subtrees, the one at `level` being set if bit `level` of `count` is.
+    inner: [T; 32],
+    mutated: bool,
+}
+
+impl<T: Hash> Default for MerkleBuilder<T> {
+    fn default() -> Self {
+        MerkleBuilder::new()
+    }
+}
+
+impl<T: Hash> MerkleBuilder<T> {
+    /// Creates a builder without any leaves.
+    pub fn new() -> MerkleBuilder<T> {
+        MerkleBuilder {
+            count: 0,
+            inner: [T::all_zeros(); 32],
+            mutated: false,
+        }
+    }
+
+    /// Returns the number of leaves pushed so far.
+    pub fn len(&self) -> u32 {
+        self.count
+    }
+
+    /// Whether no leaves were pushed yet.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Adds the next leaf of the tree.
+    ///
+    /// # Panics
+    ///
+    /// If more than `u32::MAX` leaves are pushed.
+    pub fn push(&mut self, leaf: T) {
+        self.count = self.count.checked_add(1).expect("too many merkle tree leaves");
+        let mut hash = leaf;
+        let mut level = 0;
+        while self.count & (1 << level) == 0 {
+            self.mutated |= self.inner[level] == hash;
+            hash = parent(&self.inner[level], &hash);
+            level += 1;
+        }
+        self.inner[level] = hash;
+    }
+
+    /// Returns the merkle root and whether the tree is mutated, or `None` if no leaves were
+    /// pushed.
+    pub fn finish(self) -> Option<(T, bool)> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let mut count = u64::from(self.count);
+        let mut mutated = self.mutated;
+        let mut level = self.count.trailing_zeros() as usize;
+        let mut hash = self.inner[level];
+        // Walk up the rightmost branch, pairing the odd nodes with themselves.
+        while count != 1 << level {
+            hash = parent(&hash, &hash);
+            count += 1 << level;
+            level += 1;
+            while count & (1 << level) == 0 {
+                mutated |= self.inner[level] == hash;
+                hash = parent(&self.inner[level], &hash);
+                level += 1;
+            }
+        }
+        Some((hash, mutated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256d;
+
+    // Transactions of block 100000
+    const BLOCK_100000: [&str; 4] = [
+        "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+    ];
+    const BLOCK_100000_ROOT: &str = "f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766";
+
+    fn txids(hex: &[&str]) -> [sha256d::Hash; 4] {
+        let mut ret = [sha256d::Hash::all_zeros(); 4];
+        for (txid, hex) in ret.iter_mut().zip(hex) {
+            *txid = hex.parse().unwrap();
+        }
+        ret
+    }
+
+    fn check(leaves: &[sha256d::Hash], root: sha256d::Hash, mutated: bool) {
+        assert_eq!(compute_root(leaves.iter().cloned()), Some((root, mutated)));
+        let mut scratch = [sha256d::Hash::all_zeros(); 4];
+        scratch[..leaves.len()].copy_from_slice(leaves);
+        assert_eq!(compute_root_in_place(&mut scratch[..leaves.len()]), Some((root, mutated)));
+    }
+
+    #[test]
+    fn real_blocks() {
+        let txids = txids(&BLOCK_100000);
+        check(&txids, BLOCK_100000_ROOT.parse().unwrap(), false);
+
+        // Block 170, with the first transaction between two people
+        let txids = [
+            "b1fea52486ce0c62bb442b530a3f0132b826c74e473d1f2c220bfa78111c5082".parse().unwrap(),
+            "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16".parse().unwrap(),
+        ];
+        check(&txids, "7dac2c5666815c17a3b36427de37bb9d2e2c5ccec3f8633eb91a4205cb4c10ff".parse().unwrap(), false);
+
+        // A single leaf is its own root, e.g. the coinbase of the genesis block
+        let coinbase = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b".parse().unwrap();
+        check(&[coinbase], coinbase, false);
+
+        assert_eq!(compute_root::<sha256d::Hash, _>(None), None);
+        assert_eq!(compute_root_in_place::<sha256d::Hash>(&mut []), None);
+    }
+
+    #[test]
+    fn mutation() {
+        let txids = txids(&BLOCK_100000);
+
+        // Duplicating the last transaction of an odd list does not change the root
+        let (root, mutated) = compute_root(txids[..3].iter().cloned()).unwrap();
+        assert!(!mutated);
+        let duplicated = [txids[0], txids[1], txids[2], txids[2]];
+        check(&duplicated, root, true);
+
+        // Neither does duplicating the last two
+        let duplicated = [txids[0], txids[1], txids[0], txids[1]];
+        let (root, mutated) = compute_root(txids[..2].iter().cloned()).unwrap();
+        assert!(!mutated);
+        check(&duplicated, parent(&root, &root), true);
+    }
+
+    #[test]
+    fn builder_matches_in_place() {
+        let mut leaves = [sha256d::Hash::all_zeros(); 70];
+        for (i, leaf) in leaves.iter_mut().enumerate() {
+            *leaf = sha256d::Hash::hash(&[i as u8]);
+        }
+        for len in 1..leaves.len() {
+            let mut builder = MerkleBuilder::new();
+            for leaf in leaves[..len].iter() {
+                builder.push(*leaf);
+            }
+            assert_eq!(builder.len() as usize, len);
+            let mut scratch = leaves;
+            assert_eq!(builder.finish(), compute_root_in_place(&mut scratch[..len]));
+        }
+
+        // Identical siblings found while merging the rightmost branch
+        let (a, b) = (leaves[0], leaves[1]);
+        let mut mutated = [a, b, a, b, a, b];
+        assert!(compute_root(mutated.iter().cloned()).unwrap().1);
+        assert!(compute_root_in_place(&mut mutated).unwrap().1);
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use crate::{Hash, sha256d};
+
+    #[bench]
+    pub fn merkle_root_2000(bh: &mut Bencher) {
+        let mut leaves = [sha256d::Hash::all_zeros(); 2000];
+        for (i, leaf) in leaves.iter_mut().enumerate() {
+            *leaf = sha256d::Hash::hash(&(i as u32).to_le_bytes());
+        }
+        bh.iter( || {
+            let mut scratch = leaves;
+            super::compute_root_in_place(&mut scratch)
+        });
+    }
+}
@@ -0,0 +1,528 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! BIP37 partial merkle trees.
+//!
+//! The proof carried by a `merkleblock` message that some transactions are in a block: a depth
+//! first traversal of the merkle tree, pruned below the subtrees without matched transactions,
+//! as a list of flag bits and a list of hashes. The block header is left to the caller, who
+//! should compare its merkle root with the one returned by
+//! [`PartialMerkleTree::extract_matches`].
+//!
+
+use core::fmt;
+
+use crate::alloc::vec::Vec;
+use crate::merkle::parent;
+use crate::{sha256d, Hash};
+
+/// Maximum number of transactions of a block, as in Bitcoin Core: the maximum block weight
+/// divided by the minimum transaction weight.
+pub const MAX_TRANSACTIONS: u32 = 4_000_000 / 240;
+
+/// Error of decoding or verifying a partial merkle tree.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The tree has no transactions.
+    NoTransactions,
+    /// The tree has more transactions than a block can hold.
+    TooManyTransactions,
+    /// The tree has more hashes than transactions.
+    TooManyHashes,
+    /// The tree has fewer flag bits than hashes.
+    NotEnoughBits,
+    /// The traversal needed more flag bits than the tree has.
+    BitsArrayOverflow,
+    /// The traversal needed more hashes than the tree has.
+    HashesArrayOverflow,
+    /// Flag bytes were left after the traversal.
+    NotAllBitsConsumed,
+    /// Hashes were left after the traversal.
+    NotAllHashesConsumed,
+    /// Two sibling nodes are identical, which allows mutating the list of transactions
+    /// without changing the root (CVE-2012-2459).
+    IdenticalHashesFound,
+    /// The serialized tree ended early.
+    UnexpectedEof,
+    /// A length of the serialized tree is not encoded in its shortest form.
+    NonMinimalVarInt,
+    /// Bytes were left after the serialized tree.
+    TrailingData,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NoTransactions => f.write_str("partial merkle tree has no transactions"),
+            Error::TooManyTransactions => f.write_str("partial merkle tree has too many transactions"),
+            Error::TooManyHashes => f.write_str("partial merkle tree has more hashes than transactions"),
+            Error::NotEnoughBits => f.write_str("partial merkle tree has fewer bits than hashes"),
+            Error::BitsArrayOverflow => f.write_str("partial merkle tree overflowed its bits"),
+            Error::HashesArrayOverflow => f.write_str("partial merkle tree overflowed its hashes"),
+            Error::NotAllBitsConsumed => f.write_str("partial merkle tree has unused bits"),
+            Error::NotAllHashesConsumed => f.write_str("partial merkle tree has unused hashes"),
+            Error::IdenticalHashesFound => f.write_str("partial merkle tree has identical sibling nodes"),
+            Error::UnexpectedEof => f.write_str("unexpected end of partial merkle tree"),
+            Error::NonMinimalVarInt => f.write_str("non-minimal varint"),
+            Error::TrailingData => f.write_str("trailing data after partial merkle tree"),
+        }
+    }
+}
+
+/// A BIP37 partial merkle tree.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PartialMerkleTree {
+    /// Number of transactions in the block.
+    num_transactions: u32,
+    /// Whether each traversed node is, or is an ancestor of, a matched transaction.
+    bits: Vec<bool>,
+    /// Hashes of the leaves and pruned subtrees, in traversal order.
+    hashes: Vec<sha256d::Hash>,
+}
+
+impl PartialMerkleTree {
+    /// Builds the tree proving which of the transactions with ids `txids`, in block order, are
+    /// in `matches`.
+    ///
+    /// # Panics
+    ///
+    /// If `txids` is empty or longer than `u32::MAX`, or if `matches` has a different length.
+    pub fn from_txids(txids: &[sha256d::Hash], matches: &[bool]) -> PartialMerkleTree {
+        assert!(!txids.is_empty(), "a block has at least one transaction");
+        assert!(txids.len() <= u32::max_value() as usize, "too many transactions");
+        assert_eq!(txids.len(), matches.len(), "one match flag is needed per transaction");
+
+        let mut tree = PartialMerkleTree {
+            num_transactions: txids.len() as u32,
+            bits: Vec::new(),
+            hashes: Vec::new(),
+        };
+        let height = tree.height();
+        tree.build(height, 0, txids, matches);
+        tree
+    }
+
+    /// Returns the number of transactions in the block.
+    pub fn num_transactions(&self) -> u32 {
+        self.num_transactions
+    }
+
+    /// Returns the flag bits of the traversal.
+    ///
+    /// A deserialized tree has a multiple of 8 bits, the last ones being padding.
+    pub fn bits(&self) -> &[bool] {
+        &self.bits
+    }
+
+    /// Returns the hashes of the leaves and pruned subtrees, in traversal order.
+    pub fn hashes(&self) -> &[sha256d::Hash] {
+        &self.hashes
+    }
+
+    /// Serializes the tree as in a `merkleblock` message, after the block header.
+    pub fn serialize(&self) -> Vec<u8> {
+        let n_bytes = (self.bits.len() + 7) / 8;
+        let mut out = Vec::with_capacity(4 + 9 + self.hashes.len() * 32 + 9 + n_bytes);
+        out.extend_from_slice(&self.num_transactions.to_le_bytes());
+        write_compact_size(&mut out, self.hashes.len() as u64);
+        for hash in &self.hashes {
+            out.extend_from_slice(&hash[..]);
+        }
+        write_compact_size(&mut out, n_bytes as u64);
+        let start = out.len();
+        out.resize(start + n_bytes, 0);
+        for (i, bit) in self.bits.iter().enumerate() {
+            out[start + i / 8] |= (*bit as u8) << (i % 8);
+        }
+        out
+    }
+
+    /// Deserializes a tree serialized as in a `merkleblock` message.
+    ///
+    /// This only checks the encoding; the tree itself is checked by
+    /// [`extract_matches`](PartialMerkleTree::extract_matches).
+    pub fn deserialize(mut data: &[u8]) -> Result<PartialMerkleTree, Error> {
+        let mut num_transactions = [0; 4];
+        num_transactions.copy_from_slice(take(&mut data, 4)?);
+
+        let n_hashes = read_compact_size(&mut data)?;
+        if n_hashes > (data.len() / 32) as u64 {
+            return Err(Error::UnexpectedEof);
+        }
+        let hashes = take(&mut data, n_hashes as usize * 32)?
+            .chunks(32)
+            .map(|hash| sha256d::Hash::from_slice(hash).expect("chunks are 32 bytes"))
+            .collect();
+
+        let n_bytes = read_compact_size(&mut data)?;
+        if n_bytes > data.len() as u64 {
+            return Err(Error::UnexpectedEof);
+        }
+        let bits = take(&mut data, n_bytes as usize)?
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .collect();
+
+        if !data.is_empty() {
+            return Err(Error::TrailingData);
+        }
+        Ok(PartialMerkleTree {
+            num_transactions: u32::from_le_bytes(num_transactions),
+            bits,
+            hashes,
+        })
+    }
+
+    /// Checks the tree, extracting the ids of the matched transactions into `matches` and their
+    /// positions in the block into `indexes`.
+    ///
+    /// Both vectors are cleared first. On success, returns the merkle root, which must still be
+    /// compared with the one of the block header.
+    pub fn extract_matches(
+        &self,
+        matches: &mut Vec<sha256d::Hash>,
+        indexes: &mut Vec<u32>,
+    ) -> Result<sha256d::Hash, Error> {
+        matches.clear();
+        indexes.clear();
+
+        if self.num_transactions == 0 {
+            return Err(Error::NoTransactions);
+        }
+        if self.num_transactions > MAX_TRANSACTIONS {
+            return Err(Error::TooManyTransactions);
+        }
+        if self.hashes.len() > self.num_transactions as usize {
+            return Err(Error::TooManyHashes);
+        }
+        if self.bits.len() < self.hashes.len() {
+            return Err(Error::NotEnoughBits);
+        }
+
+        let mut bits_used = 0;
+        let mut hashes_used = 0;
+        let root = self.extract(self.height(), 0, &mut bits_used, &mut hashes_used, matches, indexes)?;
+        // Only the padding of the last byte may be left.
+        if (bits_used + 7) / 8 != (self.bits.len() + 7) / 8 {
+            return Err(Error::NotAllBitsConsumed);
+        }
+        if hashes_used != self.hashes.len() {
+            return Err(Error::NotAllHashesConsumed);
+        }
+        Ok(root)
+    }
+
+    /// Returns the height of the tree, its leaves being at height 0.
+    fn height(&self) -> u32 {
+        let mut height = 0;
+        while self.width(height) > 1 {
+            height += 1;
+        }
+        height
+    }
+
+    /// Returns the number of nodes at `height`.
+    fn width(&self, height: u32) -> u32 {
+        ((u64::from(self.num_transactions) + (1 << height) - 1) >> height) as u32
+    }
+
+    /// Computes the hash of the node at `height` and `pos`.
+    fn node_hash(&self, height: u32, pos: u32, txids: &[sha256d::Hash]) -> sha256d::Hash {
+        if height == 0 {
+            return txids[pos as usize];
+        }
+        let left = self.node_hash(height - 1, pos * 2, txids);
+        let right = if pos * 2 + 1 < self.width(height - 1) {
+            self.node_hash(height - 1, pos * 2 + 1, txids)
+        } else {
+            left
+        };
+        parent(&left, &right)
+    }
+
+    /// Traverses the node at `height` and `pos`, recording its flag bit and, for a leaf or a
+    /// pruned subtree, its hash.
+    fn build(&mut self, height: u32, pos: u32, txids: &[sha256d::Hash], matches: &[bool]) {
+        let start = (pos as usize) << height;
+        let end = core::cmp::min(((pos as usize) + 1) << height, txids.len());
+        let parent_of_match = matches[start..end].iter().any(|m| *m);
+        self.bits.push(parent_of_match);
+
+        if height == 0 || !parent_of_match {
+            let hash = self.node_hash(height, pos, txids);
+            self.hashes.push(hash);
+        } else {
+            self.build(height - 1, pos * 2, txids, matches);
+            if pos * 2 + 1 < self.width(height - 1) {
+                self.build(height - 1, pos * 2 + 1, txids, matches);
+            }
+        }
+    }
+
+    /// Traverses the node at `height` and `pos` like [`build`](Self::build) did, returning its
+    /// hash.
+    fn extract(
+        &self,
+        height: u32,
+        pos: u32,
+        bits_used: &mut usize,
+        hashes_used: &mut usize,
+        matches: &mut Vec<sha256d::Hash>,
+        indexes: &mut Vec<u32>,
+    ) -> Result<sha256d::Hash, Error> {
+        let parent_of_match = *self.bits.get(*bits_used).ok_or(Error::BitsArrayOverflow)?;
+        *bits_used += 1;
+
+        if height == 0 || !parent_of_match {
+            let hash = *self.hashes.get(*hashes_used).ok_or(Error::HashesArrayOverflow)?;
+            *hashes_used += 1;
+            if height == 0 && parent_of_match {
+                matches.push(hash);
+                indexes.push(pos);
+            }
+            Ok(hash)
+        } else {
+            let left = self.extract(height - 1, pos * 2, bits_used, hashes_used, matches, indexes)?;
+            let right = if pos * 2 + 1 < self.width(height - 1) {
+                let right = self.extract(height - 1, pos * 2 + 1, bits_used, hashes_used, matches, indexes)?;
+                if right == left {
+                    return Err(Error::IdenticalHashesFound);
+                }
+                right
+            } else {
+                left
+            };
+            Ok(parent(&left, &right))
+        }
+    }
+}
+
+/// Splits the first `len` bytes off `data`.
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if data.len() < len {
+        return Err(Error::UnexpectedEof);
+    }
+    let (head, tail) = data.split_at(len);
+    *data = tail;
+    Ok(head)
+}
+
+fn read_compact_size(data: &mut &[u8]) -> Result<u64, Error> {
+    let (width, min) = match take(data, 1)?[0] {
+        0xfd => (2, 0xfd),
+        0xfe => (4, 0x10000),
+        0xff => (8, 0x100000000),
+        n => return Ok(u64::from(n)),
+    };
+    let mut bytes = [0; 8];
+    bytes[..width].copy_from_slice(take(data, width)?);
+    let n = u64::from_le_bytes(bytes);
+    if n < min {
+        return Err(Error::NonMinimalVarInt);
+    }
+    Ok(n)
+}
+
+fn write_compact_size(out: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x10000..=0xffffffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::FromHex;
+    use crate::merkle;
+
+    // Transactions of block 100000
+    const BLOCK_100000: [&str; 4] = [
+        "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+    ];
+    const BLOCK_100000_ROOT: &str = "f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766";
+
+    fn txids() -> Vec<sha256d::Hash> {
+        BLOCK_100000.iter().map(|hex| hex.parse().unwrap()).collect()
+    }
+
+    fn extract(tree: &PartialMerkleTree) -> Result<(sha256d::Hash, Vec<sha256d::Hash>, Vec<u32>), Error> {
+        let mut matches = Vec::new();
+        let mut indexes = Vec::new();
+        let root = tree.extract_matches(&mut matches, &mut indexes)?;
+        Ok((root, matches, indexes))
+    }
+
+    #[test]
+    fn block_100000() {
+        let txids = txids();
+        let tree = PartialMerkleTree::from_txids(&txids, &[false, true, false, true]);
+        assert_eq!(tree.bits(), &[true, true, false, true, true, false, true]);
+        assert_eq!(tree.hashes(), &txids[..]);
+        let decoded = PartialMerkleTree::deserialize(&tree.serialize()).unwrap();
+        assert_eq!(decoded.num_transactions(), 4);
+        assert_eq!(decoded.bits().len(), 8);
+        assert_eq!(decoded.hashes(), &txids[..]);
+
+        let (root, matches, indexes) = extract(&decoded).unwrap();
+        assert_eq!(root.to_string(), BLOCK_100000_ROOT);
+        assert_eq!(matches, [txids[1], txids[3]]);
+        assert_eq!(indexes, [1, 3]);
+
+        // Only the last transaction of three, whose sibling is itself
+        let tree = PartialMerkleTree::from_txids(&txids[..3], &[false, false, true]);
+        let (root, matches, indexes) = extract(&tree).unwrap();
+        assert_eq!(root, merkle::compute_root(txids[..3].iter().cloned()).unwrap().0);
+        assert_eq!(matches, [txids[2]]);
+        assert_eq!(indexes, [2]);
+    }
+
+    #[test]
+    fn gettxoutproof() {
+        // Output of Bitcoin Core's `gettxoutproof` for one transaction of a mainnet block: the
+        // block header followed by the partial merkle tree
+        let proof = Vec::from_hex(
+            "0100000090f0a9f110702f808219ebea1173056042a714bad51b916cb6800000000000005275289558f51c\
+             9966699404ae2294730c3c9f9bda53523ce50e9b95e558da2fdb261b4d4c86041b1ab1bf930900000005fac\
+             7708a6e81b2a986dea60db2663840ed141130848162eb1bd1dee54f309a1b2ee1e12587e497ada70d9bd10d\
+             31e83f0a924825b96cb8d04e8936d793fb60db7ad8b910d0c7ba2369bc7f18bb53d80e1869ba2c32274996c\
+             ebe1ae264bc0e2289189ff0316cdc10511da71da757e553cada9f3b5b1434f3923673adb57d83caac392c38\
+             af156d6fc30b55fad4112df2b95531e68114e9ad10011e72f7b7cfdb025700"
+        ).unwrap();
+        let (header, tree_bytes) = proof.split_at(80);
+        assert_eq!(
+            sha256d::Hash::hash(header).to_string(),
+            "0000000000013b8ab2cd513b0261a14096412195a72a0c4827d229dcc7e0f7af",
+        );
+        let merkle_root = sha256d::Hash::from_slice(&header[36..68]).unwrap();
+
+        let tree = PartialMerkleTree::deserialize(tree_bytes).unwrap();
+        assert_eq!(tree.num_transactions(), 9);
+        assert_eq!(tree.hashes().len(), 5);
+        assert_eq!(tree.serialize(), tree_bytes);
+
+        let (root, matches, indexes) = extract(&tree).unwrap();
+        assert_eq!(root, merkle_root);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].to_string(), "220ebc64e21abece964927322cba69180ed853bb187fbc6923bac7d010b9d87a");
+        assert_eq!(indexes, [3]);
+    }
+
+    #[test]
+    fn all_match_patterns() {
+        let txids: Vec<sha256d::Hash> = (0..13u8).map(|i| sha256d::Hash::hash(&[i])).collect();
+        for len in 1..txids.len() {
+            let root = merkle::compute_root(txids[..len].iter().cloned()).unwrap().0;
+            for pattern in 0..(1u32 << len) {
+                let flags: Vec<bool> = (0..len).map(|i| pattern & (1 << i) != 0).collect();
+                let tree = PartialMerkleTree::from_txids(&txids[..len], &flags);
+                let tree = PartialMerkleTree::deserialize(&tree.serialize()).unwrap();
+
+                let (extracted, matches, indexes) = extract(&tree).unwrap();
+                assert_eq!(extracted, root);
+                let expected: Vec<u32> = (0..len as u32).filter(|i| flags[*i as usize]).collect();
+                assert_eq!(indexes, expected);
+                let expected: Vec<sha256d::Hash> = expected.iter().map(|i| txids[*i as usize]).collect();
+                assert_eq!(matches, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let txids = txids();
+        let tree = PartialMerkleTree::from_txids(&txids, &[false, true, false, true]);
+
+        let mut bad = tree.clone();
+        bad.num_transactions = 0;
+        assert_eq!(extract(&bad), Err(Error::NoTransactions));
+        bad.num_transactions = MAX_TRANSACTIONS + 1;
+        assert_eq!(extract(&bad), Err(Error::TooManyTransactions));
+
+        let mut bad = tree.clone();
+        bad.hashes.push(txids[0]);
+        assert_eq!(extract(&bad), Err(Error::TooManyHashes));
+        bad.num_transactions = 5;
+        bad.bits.truncate(4);
+        assert_eq!(extract(&bad), Err(Error::NotEnoughBits));
+
+        let mut bad = tree.clone();
+        bad.hashes.pop();
+        assert_eq!(extract(&bad), Err(Error::HashesArrayOverflow));
+
+        let mut bad = tree.clone();
+        bad.bits.truncate(6);
+        assert_eq!(extract(&bad), Err(Error::BitsArrayOverflow));
+
+        // A whole unused byte of bits, while unused bits of the last byte are padding
+        let mut bad = tree.clone();
+        bad.bits.extend_from_slice(&[false; 9]);
+        assert_eq!(extract(&bad), Err(Error::NotAllBitsConsumed));
+        bad.bits.truncate(8);
+        assert!(extract(&bad).is_ok());
+
+        // An extra hash after the ones of the traversal
+        let mut bad = PartialMerkleTree::from_txids(&txids, &[false, false, false, true]);
+        bad.hashes.insert(1, txids[1]);
+        assert_eq!(extract(&bad), Err(Error::NotAllHashesConsumed));
+
+        // Duplicating the last transaction of three, as in CVE-2012-2459
+        let mutated = [txids[0], txids[1], txids[2], txids[2]];
+        let bad = PartialMerkleTree::from_txids(&mutated, &[false, false, true, true]);
+        assert_eq!(extract(&bad), Err(Error::IdenticalHashesFound));
+    }
+
+    #[test]
+    fn deserialize_errors() {
+        let tree = PartialMerkleTree::from_txids(&txids(), &[true; 4]).serialize();
+
+        for len in 0..tree.len() {
+            assert_eq!(PartialMerkleTree::deserialize(&tree[..len]), Err(Error::UnexpectedEof));
+        }
+        let mut trailing = tree.clone();
+        trailing.push(0);
+        assert_eq!(PartialMerkleTree::deserialize(&trailing), Err(Error::TrailingData));
+
+        // A huge number of hashes is rejected before allocating
+        assert_eq!(
+            PartialMerkleTree::deserialize(&[1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]),
+            Err(Error::UnexpectedEof),
+        );
+        assert_eq!(
+            PartialMerkleTree::deserialize(&[1, 0, 0, 0, 0xfd, 0x01, 0x00]),
+            Err(Error::NonMinimalVarInt),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_txids_length_mismatch() {
+        PartialMerkleTree::from_txids(&txids(), &[true]);
+    }
+}
@@ -0,0 +1,420 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! MuHash3072 implementation.
+//!
+//! MuHash is a rolling hash of a set: elements can be added and removed in any order, and two
+//! sets with the same elements always hash to the same value. This is the construction Bitcoin
+//! Core uses for the `muhash` UTXO set hash of `gettxoutsetinfo`.
+//!
+//! Each element is hashed with SHA256, the digest is expanded to 3072 bits with ChaCha20, and
+//! the result is interpreted as a number modulo the prime 2^3072 - 1103717. The set hash is the
+//! product of the numbers of its elements, kept as a fraction so that removals do not need a
+//! modular inversion until the hash is finalized.
+//!
+
+use core::fmt;
+
+use crate::{sha256, Hash, HashEngine};
+
+/// Number of 64-bit limbs of a [`Num3072`].
+const LIMBS: usize = 48;
+
+/// Size of a serialized number, in bytes.
+const BYTE_SIZE: usize = LIMBS * 8;
+
+/// The modulus is `2^3072 - MAX_PRIME_DIFF`.
+const MAX_PRIME_DIFF: u64 = 1103717;
+
+/// A 3072-bit number, always kept fully reduced modulo the MuHash prime.
+#[derive(Clone, Copy)]
+struct Num3072 {
+    limbs: [u64; LIMBS],
+}
+
+impl PartialEq for Num3072 {
+    fn eq(&self, other: &Num3072) -> bool {
+        self.limbs[..] == other.limbs[..]
+    }
+}
+
+impl Eq for Num3072 {}
+
+impl Num3072 {
+    fn one() -> Num3072 {
+        let mut limbs = [0; LIMBS];
+        limbs[0] = 1;
+        Num3072 { limbs }
+    }
+
+    /// Interprets `bytes` as a little-endian number, reducing it modulo the prime.
+    fn from_le_bytes(bytes: &[u8; BYTE_SIZE]) -> Num3072 {
+        let mut limbs = [0; LIMBS];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+            let mut buf = [0; 8];
+            buf.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(buf);
+        }
+        let mut ret = Num3072 { limbs };
+        ret.reduce_if_overflow();
+        ret
+    }
+
+    fn to_le_bytes(self) -> [u8; BYTE_SIZE] {
+        let mut ret = [0; BYTE_SIZE];
+        for (limb, chunk) in self.limbs.iter().zip(ret.chunks_exact_mut(8)) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        ret
+    }
+
+    /// Whether the number is at least the prime (it is always less than 2^3072).
+    fn is_overflow(&self) -> bool {
+        self.limbs[0] > u64::max_value() - MAX_PRIME_DIFF
+            && self.limbs[1..].iter().all(|limb| *limb == u64::max_value())
+    }
+
+    /// Subtracts the prime if the number is not fully reduced, which is done by adding
+    /// `MAX_PRIME_DIFF` and dropping the carry out of the top limb.
+    fn reduce_if_overflow(&mut self) {
+        if self.is_overflow() {
+            self.add_small(MAX_PRIME_DIFF);
+        }
+    }
+
+    /// Adds `n`, returning the carry out of the top limb.
+    fn add_small(&mut self, n: u64) -> bool {
+        let mut carry = n;
+        for limb in self.limbs.iter_mut() {
+            if carry == 0 {
+                break;
+            }
+            let (sum, overflow) = limb.overflowing_add(carry);
+            *limb = sum;
+            carry = overflow as u64;
+        }
+        carry != 0
+    }
+
+    fn mul(&self, other: &Num3072) -> Num3072 {
+        // Schoolbook multiplication into a 6144-bit product.
+        let mut product = [0u64; 2 * LIMBS];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let t = u128::from(*a) * u128::from(*b) + u128::from(product[i + j]) + carry;
+                product[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            product[i + LIMBS] = carry as u64;
+        }
+
+        // Since 2^3072 = MAX_PRIME_DIFF mod p, fold the high half onto the low one: the result
+        // overflows 3072 bits by less than 2^22.
+        let mut ret = Num3072 { limbs: [0; LIMBS] };
+        let mut carry = 0u128;
+        for i in 0..LIMBS {
+            let t = u128::from(product[i]) + u128::from(product[i + LIMBS]) * u128::from(MAX_PRIME_DIFF) + carry;
+            ret.limbs[i] = t as u64;
+            carry = t >> 64;
+        }
+
+        // Fold the remaining top bits the same way. If that carries out of the top limb once
+        // more, the number is now tiny and adding MAX_PRIME_DIFF cannot carry again.
+        if ret.add_small(carry as u64 * MAX_PRIME_DIFF) {
+            ret.add_small(MAX_PRIME_DIFF);
+        }
+        ret.reduce_if_overflow();
+        ret
+    }
+
+    /// Computes the modular inverse as `self^(p - 2)`, using a fixed 4-bit window.
+    fn inverse(&self) -> Num3072 {
+        let mut table = [Num3072::one(); 16];
+        for i in 1..16 {
+            table[i] = table[i - 1].mul(self);
+        }
+
+        // The exponent p - 2 = 2^3072 - MAX_PRIME_DIFF - 2, most significant limb first.
+        let mut ret = Num3072::one();
+        for i in (0..LIMBS).rev() {
+            let limb = if i == 0 { u64::max_value() - MAX_PRIME_DIFF - 1 } else { u64::max_value() };
+            for window in (0..16).rev() {
+                for _ in 0..4 {
+                    ret = ret.mul(&ret);
+                }
+                ret = ret.mul(&table[((limb >> (4 * window)) & 0xf) as usize]);
+            }
+        }
+        ret
+    }
+}
+
+/// Fills `out` with the ChaCha20 keystream for `key`, with an all-zero nonce.
+fn chacha20_keystream(key: &[u8; 32], out: &mut [u8]) {
+    macro_rules! quarter_round {
+        ($s:expr, $a:expr, $b:expr, $c:expr, $d:expr) => {
+            $s[$a] = $s[$a].wrapping_add($s[$b]); $s[$d] = ($s[$d] ^ $s[$a]).rotate_left(16);
+            $s[$c] = $s[$c].wrapping_add($s[$d]); $s[$b] = ($s[$b] ^ $s[$c]).rotate_left(12);
+            $s[$a] = $s[$a].wrapping_add($s[$b]); $s[$d] = ($s[$d] ^ $s[$a]).rotate_left(8);
+            $s[$c] = $s[$c].wrapping_add($s[$d]); $s[$b] = ($s[$b] ^ $s[$c]).rotate_left(7);
+        };
+    }
+
+    let mut input = [0u32; 16];
+    input[..4].copy_from_slice(&[0x61707865, 0x3320646e, 0x79622d32, 0x6b206574]);
+    for (word, chunk) in input[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+
+    for (counter, block) in out.chunks_mut(64).enumerate() {
+        input[12] = counter as u32;
+        input[13] = (counter as u64 >> 32) as u32;
+
+        let mut s = input;
+        for _ in 0..10 {
+            quarter_round!(s, 0, 4, 8, 12);
+            quarter_round!(s, 1, 5, 9, 13);
+            quarter_round!(s, 2, 6, 10, 14);
+            quarter_round!(s, 3, 7, 11, 15);
+            quarter_round!(s, 0, 5, 10, 15);
+            quarter_round!(s, 1, 6, 11, 12);
+            quarter_round!(s, 2, 7, 8, 13);
+            quarter_round!(s, 3, 4, 9, 14);
+        }
+
+        for (i, chunk) in block.chunks_mut(4).enumerate() {
+            let word = s[i].wrapping_add(input[i]).to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+    }
+}
+
+/// Maps a set element to a number modulo the prime.
+fn element_to_num3072(data: &[u8]) -> Num3072 {
+    let digest = sha256::Hash::hash(data);
+    let mut bytes = [0; BYTE_SIZE];
+    chacha20_keystream(&digest.into_inner(), &mut bytes);
+    Num3072::from_le_bytes(&bytes)
+}
+
+/// A MuHash3072 set hash under construction.
+///
+/// The default value is the hash of the empty set. Note that the same set can be represented by
+/// different `MuHash3072` values, so they are compared by [`finalize`](MuHash3072::finalize)-ing
+/// them.
+#[derive(Clone)]
+pub struct MuHash3072 {
+    numerator: Num3072,
+    denominator: Num3072,
+}
+
+impl MuHash3072 {
+    /// Creates the hash of the empty set.
+    pub fn new() -> MuHash3072 {
+        MuHash3072 {
+            numerator: Num3072::one(),
+            denominator: Num3072::one(),
+        }
+    }
+
+    /// Adds an element to the set.
+    pub fn insert(&mut self, data: &[u8]) {
+        self.numerator = self.numerator.mul(&element_to_num3072(data));
+    }
+
+    /// Removes an element from the set.
+    ///
+    /// The element does not have to be in the set already: removals and insertions cancel out
+    /// in whichever order they happen.
+    pub fn remove(&mut self, data: &[u8]) {
+        self.denominator = self.denominator.mul(&element_to_num3072(data));
+    }
+
+    /// Adds all the elements of `other` to this set (and removes the ones it removes).
+    pub fn combine(&mut self, other: &MuHash3072) {
+        self.numerator = self.numerator.mul(&other.numerator);
+        self.denominator = self.denominator.mul(&other.denominator);
+    }
+
+    /// Removes all the elements of `other` from this set (and adds the ones it removes).
+    pub fn subtract(&mut self, other: &MuHash3072) {
+        self.numerator = self.numerator.mul(&other.denominator);
+        self.denominator = self.denominator.mul(&other.numerator);
+    }
+
+    /// Computes the 256-bit hash of the set, as reported by Bitcoin Core.
+    ///
+    /// Note that Bitcoin Core displays this hash in reverse byte order, like `uint256`s in
+    /// general.
+    pub fn finalize(&self) -> sha256::Hash {
+        let num = self.numerator.mul(&self.denominator.inverse());
+        let mut engine = sha256::Hash::engine();
+        engine.input(&num.to_le_bytes());
+        sha256::Hash::from_engine(engine)
+    }
+}
+
+impl Default for MuHash3072 {
+    fn default() -> Self {
+        MuHash3072::new()
+    }
+}
+
+impl fmt::Debug for MuHash3072 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MuHash3072({})", self.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::FromHex;
+
+    /// Element `i` of Bitcoin Core's tests: a 32-byte buffer starting with `i`.
+    fn element(i: u8) -> [u8; 32] {
+        let mut ret = [0; 32];
+        ret[0] = i;
+        ret
+    }
+
+    fn from_element(i: u8) -> MuHash3072 {
+        let mut ret = MuHash3072::new();
+        ret.insert(&element(i));
+        ret
+    }
+
+    /// Parses a `uint256` as displayed by Bitcoin Core, which reverses the bytes.
+    fn core_uint256(s: &str) -> sha256::Hash {
+        let mut bytes = <[u8; 32]>::from_hex(s).expect("parse hex");
+        bytes.reverse();
+        sha256::Hash::from_inner(bytes)
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn chacha20() {
+        // RFC 7539 section 2.3.2 with an all-zero key and nonce.
+        let mut out = [0u8; 64];
+        chacha20_keystream(&[0; 32], &mut out);
+        assert_eq!(
+            out[..],
+            Vec::from_hex(
+                "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7\
+                 da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586"
+            ).unwrap()[..],
+        );
+    }
+
+    #[test]
+    fn num3072_arithmetic() {
+        let mut minus_one = Num3072 { limbs: [u64::max_value(); LIMBS] };
+        minus_one.limbs[0] -= MAX_PRIME_DIFF;
+        assert!(!minus_one.is_overflow());
+        assert!(minus_one.mul(&minus_one) == Num3072::one());
+
+        // 2^3072 - 1 reduces to MAX_PRIME_DIFF - 1
+        let max = Num3072::from_le_bytes(&[0xff; BYTE_SIZE]);
+        let mut expected = Num3072 { limbs: [0; LIMBS] };
+        expected.limbs[0] = MAX_PRIME_DIFF - 1;
+        assert!(max == expected);
+
+        let mut two = Num3072::one();
+        two.limbs[0] = 2;
+        assert!(two.mul(&two.inverse()) == Num3072::one());
+        assert!(minus_one.inverse() == minus_one);
+
+        let x = element_to_num3072(b"muhash");
+        assert!(x.mul(&x.inverse()) == Num3072::one());
+    }
+
+    #[test]
+    fn muhash() {
+        // Test vectors from Bitcoin Core's crypto_tests.cpp
+        let mut acc = from_element(0);
+        acc.combine(&from_element(1));
+        acc.subtract(&from_element(2));
+        assert_eq!(acc.finalize(), core_uint256("10d312b100cbd32ada024a6646e40d3482fcff103668d2625f10002a607d5863"));
+
+        let mut acc = from_element(0);
+        acc.insert(&element(1));
+        acc.remove(&element(2));
+        assert_eq!(acc.finalize(), core_uint256("10d312b100cbd32ada024a6646e40d3482fcff103668d2625f10002a607d5863"));
+
+        // The empty set hashes 1 serialized as a 3072-bit number
+        let mut one = [0u8; BYTE_SIZE];
+        one[0] = 1;
+        assert_eq!(MuHash3072::new().finalize(), sha256::Hash::hash(&one));
+    }
+
+    #[test]
+    fn muhash_order_independence() {
+        // Like Bitcoin Core's randomized test, but going through all the orders of a fixed
+        // sequence of insertions and removals.
+        let table = [1u8, 6, 3, 4];
+        let mut expected = None;
+        for order in 0..4 {
+            let mut acc = MuHash3072::new();
+            for i in 0..4 {
+                let t = table[i ^ order];
+                if t & 4 != 0 {
+                    acc.subtract(&from_element(t & 3));
+                } else {
+                    acc.combine(&from_element(t & 3));
+                }
+            }
+            let out = acc.finalize();
+            match expected {
+                None => expected = Some(out),
+                Some(expected) => assert_eq!(out, expected),
+            }
+        }
+
+        let x = from_element(5);
+        let mut y = from_element(9);
+        let mut z = MuHash3072::new();
+        z.combine(&x);
+        z.combine(&y);
+        y.combine(&x);
+        z.subtract(&y);
+        assert_eq!(z.finalize(), MuHash3072::new().finalize());
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use super::MuHash3072;
+
+    #[bench]
+    pub fn muhash_insert(bh: &mut Bencher) {
+        let mut acc = MuHash3072::new();
+        let bytes = [1u8; 64];
+        bh.iter(|| {
+            acc.insert(&bytes);
+        });
+    }
+
+    #[bench]
+    pub fn muhash_finalize(bh: &mut Bencher) {
+        let mut acc = MuHash3072::new();
+        acc.insert(&[1u8; 64]);
+        acc.remove(&[2u8; 64]);
+        bh.iter(|| {
+            acc.finalize()
+        });
+    }
+}
@@ -0,0 +1,152 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! Password-Based Key Derivation Function 2 (PBKDF2).
+//!
+//! Implementation of PBKDF2 from RFC 8018, using HMAC over any hash function as pseudorandom
+//! function. BIP39 seeds are derived with PBKDF2-HMAC-SHA512 and 2048 rounds.
+//!
+
+use crate::{Hash, HashEngine};
+use crate::hmac::{Hmac, HmacEngine};
+
+/// Fills `out` with the key derived from `password` and `salt` with `rounds` iterations of
+/// HMAC-`T`.
+///
+/// The HMAC engine keyed with the password is only set up once; every iteration resumes from
+/// its inner and outer midstates.
+///
+/// # Panics
+///
+/// If `rounds` is zero, or if `out` is longer than `(2^32 - 1) * T::LEN` bytes.
+pub fn derive<T: Hash>(password: &[u8], salt: &[u8], rounds: u32, out: &mut [u8]) {
+    assert!(rounds > 0, "PBKDF2 needs at least one round");
+    // The block index is a 32-bit counter starting at 1.
+    assert!(out.chunks(T::LEN).len() as u64 <= u64::from(core::u32::MAX), "derived key too long");
+
+    let keyed = HmacEngine::<T>::new(password);
+    for (index, chunk) in out.chunks_mut(T::LEN).enumerate() {
+        let mut engine = keyed.clone();
+        engine.input(salt);
+        engine.input(&(index as u32 + 1).to_be_bytes());
+        let mut u = Hmac::<T>::from_engine(engine);
+        chunk.copy_from_slice(&u[..chunk.len()]);
+
+        for _ in 1..rounds {
+            let mut engine = keyed.clone();
+            engine.input(&u[..]);
+            u = Hmac::from_engine(engine);
+            for (out_byte, u_byte) in chunk.iter_mut().zip(&u[..]) {
+                *out_byte ^= *u_byte;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use super::derive;
+        use crate::{sha1, sha256, sha512};
+        use crate::hex::ToHex;
+
+        struct Test {
+            password: &'static [u8],
+            salt: &'static [u8],
+            rounds: u32,
+            output: &'static str,
+        }
+
+        // Test vectors from RFC 6070, except the one with 16777216 rounds
+        let tests = [
+            Test {
+                password: b"password",
+                salt: b"salt",
+                rounds: 1,
+                output: "0c60c80f961f0e71f3a9b524af6012062fe037a6",
+            },
+            Test {
+                password: b"password",
+                salt: b"salt",
+                rounds: 2,
+                output: "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957",
+            },
+            Test {
+                password: b"password",
+                salt: b"salt",
+                rounds: 4096,
+                output: "4b007901b765489abead49d926f721d065a429c1",
+            },
+            Test {
+                password: b"passwordPASSWORDpassword",
+                salt: b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                rounds: 4096,
+                output: "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038",
+            },
+            Test {
+                password: b"pass\0word",
+                salt: b"sa\0lt",
+                rounds: 4096,
+                output: "56fa6aa75548099dcc37d7f03425e0c3",
+            },
+        ];
+
+        for test in tests.iter() {
+            let mut output = vec![0; test.output.len() / 2];
+            derive::<sha1::Hash>(test.password, test.salt, test.rounds, &mut output);
+            assert_eq!(output.to_hex(), test.output);
+        }
+
+        // BIP39 seed of the first English test vector, with passphrase "TREZOR"
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let mut seed = [0; 64];
+        derive::<sha512::Hash>(mnemonic.as_bytes(), b"mnemonicTREZOR", 2048, &mut seed);
+        assert_eq!(
+            seed.to_hex(),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        );
+
+        // Output spanning several blocks, checked against Python's hashlib
+        let mut output = [0; 100];
+        derive::<sha256::Hash>(b"password", b"salt", 2, &mut output);
+        assert_eq!(
+            output.to_hex(),
+            "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43830651afcb5c862f0b249bd031f7a67520d136470f5ec271ece91c07773253d93e676b079cae1219a000f8b4b1a0a3ba5ea65902f57c39e37264af9e6ce4a282b44cd732",
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_rounds() {
+        let mut output = [0; 32];
+        super::derive::<crate::sha256::Hash>(b"password", b"salt", 0, &mut output);
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use crate::sha512;
+
+    #[bench]
+    pub fn pbkdf2_sha512_bip39(bh: &mut Bencher) {
+        let mut seed = [0; 64];
+        bh.iter( || {
+            super::derive::<sha512::Hash>(b"mnemonic words", b"mnemonic", 2048, &mut seed);
+        });
+    }
+}
@@ -0,0 +1,162 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! RFC 6979 deterministic nonces.
+//!
+//! Generation of the `k` value of DSA and ECDSA signatures from the private key and the hash of
+//! the message, using an [`HmacDrbg`] over any hash function. Integers are big-endian byte
+//! strings as long as the group order.
+//!
+
+use crate::Hash;
+use crate::hmac_drbg::HmacDrbg;
+
+/// Shifts a big-endian integer right by `bits`, which must be less than 8.
+fn shift_right(num: &mut [u8], bits: u32) {
+    if bits == 0 {
+        return;
+    }
+    let mut carry = 0;
+    for byte in num.iter_mut() {
+        let next_carry = *byte << (8 - bits);
+        *byte = (*byte >> bits) | carry;
+        carry = next_carry;
+    }
+}
+
+/// Subtracts big-endian integer `b` from `a`, which must be at least as large.
+fn sub_assign(a: &mut [u8], b: &[u8]) {
+    let mut borrow = false;
+    for (a, b) in a.iter_mut().zip(b).rev() {
+        let (diff, borrow1) = a.overflowing_sub(*b);
+        let (diff, borrow2) = diff.overflowing_sub(borrow as u8);
+        *a = diff;
+        borrow = borrow1 || borrow2;
+    }
+}
+
+/// Generates the nonce `k` for signing the message with hash `msg_hash` with private key `key`,
+/// in a group of order `order`.
+///
+/// Additional data `extra_entropy` may be mixed in as described in section 3.6 of the RFC; it
+/// should be empty to get the nonces of the standard.
+///
+/// # Panics
+///
+/// If `order` is zero or has leading zero bytes, or if `key` and `k` have a different length
+/// than `order`.
+pub fn generate_k<T: Hash>(order: &[u8], key: &[u8], msg_hash: &[u8], extra_entropy: &[u8], k: &mut [u8]) {
+    assert!(order.first().map_or(false, |byte| *byte != 0), "order must not have leading zero bytes");
+    assert_eq!(key.len(), order.len(), "key must be as long as the order");
+    assert_eq!(k.len(), order.len(), "k must be as long as the order");
+
+    // Number of bits to drop from a string of `order.len()` bytes to get as many bits as the order.
+    let shift = order[0].leading_zeros();
+
+    // bits2octets(msg_hash), computed in place in `k`
+    for byte in k.iter_mut() {
+        *byte = 0;
+    }
+    if msg_hash.len() >= k.len() {
+        k.copy_from_slice(&msg_hash[..order.len()]);
+        shift_right(k, shift);
+    } else {
+        let offset = k.len() - msg_hash.len();
+        k[offset..].copy_from_slice(msg_hash);
+    }
+    if &k[..] >= order {
+        sub_assign(k, order);
+    }
+
+    let mut drbg = HmacDrbg::<T>::from_seed(&[key, k, extra_entropy]);
+    loop {
+        drbg.generate(k, &[]);
+        shift_right(k, shift);
+        if &k[..] < order && k.iter().any(|byte| *byte != 0) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::{sha1, sha256, sha512};
+        use crate::hex::{FromHex, ToHex};
+
+        fn check<T: Hash>(order: &str, key: &str, msg: &[u8], extra_entropy: &[u8], expected: &str) {
+            let order = Vec::from_hex(order).unwrap();
+            let key = Vec::from_hex(key).unwrap();
+            let mut k = vec![0; order.len()];
+            generate_k::<T>(&order, &key, &<T as Hash>::hash(msg)[..], extra_entropy, &mut k);
+            assert_eq!(k.to_hex(), expected);
+        }
+
+        // ECDSA on P-256, from RFC 6979 section A.2.5
+        let order = "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551";
+        let key = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+        check::<sha1::Hash>(order, key, b"sample", &[], "882905f1227fd620fbf2abf21244f0ba83d0dc3a9103dbbee43a1fb858109db4");
+        check::<sha256::Hash>(order, key, b"sample", &[], "a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60");
+        check::<sha512::Hash>(order, key, b"sample", &[], "5fa81c63109badb88c1f367b47da606da28cad69aa22c4fe6ad7df73a7173aa5");
+        check::<sha256::Hash>(order, key, b"test", &[], "d16b6ae827f17175e040871a1c7ec3500192c4c92677336ec2537acaee0008e0");
+
+        // Order of 163 bits, from RFC 6979 section A.1
+        check::<sha256::Hash>(
+            "04000000000000000000020108a2e0cc0d99f8a5ef",
+            "009a4d6792295a7f730fc3f2b49cbc0f62e862272f",
+            b"sample",
+            &[],
+            "023af4074c90a02b3fe61d286d5c87f425e6bdd81b",
+        );
+
+        // secp256k1
+        let order = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+        let key = "0000000000000000000000000000000000000000000000000000000000000001";
+        check::<sha256::Hash>(order, key, b"Satoshi Nakamoto", &[], "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15");
+        // With extra entropy, like Bitcoin Core when grinding for low R values
+        let mut extra_entropy = [0; 32];
+        extra_entropy[0] = 1;
+        check::<sha256::Hash>(order, key, b"Satoshi Nakamoto", &extra_entropy, "b8e91d19741f580eb14a4489493c085b7618caabcd0220cb0ac29161d9ce38a3");
+    }
+
+    #[test]
+    fn bits2octets() {
+        let mut num = [0xff, 0x01];
+        shift_right(&mut num, 1);
+        assert_eq!(num, [0x7f, 0x80]);
+
+        let mut num = [0x01, 0x00];
+        sub_assign(&mut num, &[0x00, 0x01]);
+        assert_eq!(num, [0x00, 0xff]);
+
+        // A hash larger than the order is reduced
+        let mut k = [0; 1];
+        let mut same = [0; 1];
+        generate_k::<crate::sha256::Hash>(&[0xf0], &[0x01], &[0xf1], &[], &mut k);
+        generate_k::<crate::sha256::Hash>(&[0xf0], &[0x01], &[0x01], &[], &mut same);
+        assert_eq!(k, same);
+        assert!(k[0] != 0 && k[0] < 0xf0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn wrong_key_length() {
+        let mut k = [0; 2];
+        generate_k::<crate::sha256::Hash>(&[0xf0, 0x00], &[0x01], &[0x01], &[], &mut k);
+    }
+}
@@ -34,6 +34,17 @@ crate::internal_macros::hash_type! {
     "crate::util::json_hex_string::len_20"
 }
 
+impl Hash {
+    /// Hashes `bytes` in a const context.
+    ///
+    /// This is much slower than [`Hash::hash`](crate::Hash::hash), which should be used at
+    /// runtime. Only available on Rust 1.46 and later.
+    #[cfg(rust_v_1_46)]
+    pub const fn const_hash(bytes: &[u8]) -> Hash {
+        Hash(digest_const(bytes))
+    }
+}
+
 #[cfg(not(fuzzing))]
 fn from_engine(mut e: HashEngine) -> Hash {
     // pad buffer with a single 1-bit then all 0s, until there are exactly 8 bytes remaining
@@ -63,6 +74,9 @@ fn from_engine(e: HashEngine) -> Hash {
 
 const BLOCK_SIZE: usize = 64;
 
+/// Initial state of RIPEMD160.
+const IV: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
 /// Engine to compute RIPEMD160 hash function.
 #[derive(Clone)]
 pub struct HashEngine {
@@ -74,7 +88,7 @@ pub struct HashEngine {
 impl Default for HashEngine {
     fn default() -> Self {
         HashEngine {
-            h: [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0],
+            h: IV,
             length: 0,
             buffer: [0; BLOCK_SIZE],
         }
@@ -402,8 +416,191 @@ impl HashEngine {
     }
 }
 
+/// Message word selected by each step of the left line.
+#[cfg(rust_v_1_46)]
+const R_LEFT: [usize; 80] = [
+     0,  1,  2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13, 14, 15,
+     7,  4, 13,  1, 10,  6, 15,  3, 12,  0,  9,  5,  2, 14, 11,  8,
+     3, 10, 14,  4,  9, 15,  8,  1,  2,  7,  0,  6, 13, 11,  5, 12,
+     1,  9, 11, 10,  0,  8, 12,  4, 13,  3,  7, 15, 14,  5,  6,  2,
+     4,  0,  5,  9,  7, 12,  2, 10, 14,  1,  3,  8, 11,  6, 15, 13,
+];
+
+/// Rotation of each step of the left line.
+#[cfg(rust_v_1_46)]
+const S_LEFT: [u32; 80] = [
+    11, 14, 15, 12,  5,  8,  7,  9, 11, 13, 14, 15,  6,  7,  9,  8,
+     7,  6,  8, 13, 11,  9,  7, 15,  7, 12, 15,  9, 11,  7, 13, 12,
+    11, 13,  6,  7, 14,  9, 13, 15, 14,  8, 13,  6,  5, 12,  7,  5,
+    11, 12, 14, 15, 14, 15,  9,  8,  9, 14,  5,  6,  8,  6,  5, 12,
+     9, 15,  5, 11,  6,  8, 13, 12,  5, 12, 13, 14, 11,  8,  5,  6,
+];
+
+/// Message word selected by each step of the right line.
+#[cfg(rust_v_1_46)]
+const R_RIGHT: [usize; 80] = [
+     5, 14,  7,  0,  9,  2, 11,  4, 13,  6, 15,  8,  1, 10,  3, 12,
+     6, 11,  3,  7,  0, 13,  5, 10, 14, 15,  8, 12,  4,  9,  1,  2,
+    15,  5,  1,  3,  7, 14,  6,  9, 11,  8, 12,  2, 10,  0,  4, 13,
+     8,  6,  4,  1,  3, 11, 15,  0,  5, 12,  2, 13,  9,  7, 10, 14,
+    12, 15, 10,  4,  1,  5,  8,  7,  6,  2, 13, 14,  0,  3,  9, 11,
+];
+
+/// Rotation of each step of the right line.
+#[cfg(rust_v_1_46)]
+const S_RIGHT: [u32; 80] = [
+     8,  9,  9, 11, 13, 15, 15,  5,  7,  7,  8, 11, 14, 14, 12,  6,
+     9, 13, 15,  7, 12,  8,  9, 11,  7,  7, 12,  7,  6, 15, 13, 11,
+     9,  7, 15, 11,  8,  6,  6, 14, 12, 13,  5, 14, 13, 13,  7,  5,
+    15,  5,  8, 11, 14, 14,  6, 14,  6,  9, 12,  9, 12,  5, 15,  8,
+     8,  5, 12,  9, 12,  5, 14,  6,  8, 13,  6,  5, 15, 13, 11, 11,
+];
+
+/// The boolean function of the given round, the left line using them in order and the right
+/// line in reverse.
+#[cfg(rust_v_1_46)]
+const fn f(round: usize, x: u32, y: u32, z: u32) -> u32 {
+    match round {
+        0 => x ^ y ^ z,
+        1 => (x & y) | (!x & z),
+        2 => (x | !y) ^ z,
+        3 => (x & z) | (y & !z),
+        _ => x ^ (y | !z),
+    }
+}
+
+/// Compresses `block` into `state`, in a const context.
+///
+/// This is much slower than [`HashEngine::process_block`], which should be used at runtime.
+#[cfg(rust_v_1_46)]
+const fn compress_const(state: [u32; 5], block: &[u8; BLOCK_SIZE]) -> [u32; 5] {
+    const K_LEFT: [u32; 5] = [0x00000000, 0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xa953fd4e];
+    const K_RIGHT: [u32; 5] = [0x50a28be6, 0x5c4dd124, 0x6d703ef3, 0x7a6d76e9, 0x00000000];
+
+    let mut w = [0u32; 16];
+    let mut i = 0;
+    while i < 16 {
+        w[i] = (block[4 * i] as u32)
+            | ((block[4 * i + 1] as u32) << 8)
+            | ((block[4 * i + 2] as u32) << 16)
+            | ((block[4 * i + 3] as u32) << 24);
+        i += 1;
+    }
+
+    let (mut al, mut bl, mut cl, mut dl, mut el) = (state[0], state[1], state[2], state[3], state[4]);
+    let (mut ar, mut br, mut cr, mut dr, mut er) = (state[0], state[1], state[2], state[3], state[4]);
+    let mut j = 0;
+    while j < 80 {
+        let round = j / 16;
+        let t = al.wrapping_add(f(round, bl, cl, dl)).wrapping_add(w[R_LEFT[j]]).wrapping_add(K_LEFT[round])
+            .rotate_left(S_LEFT[j]).wrapping_add(el);
+        al = el;
+        el = dl;
+        dl = cl.rotate_left(10);
+        cl = bl;
+        bl = t;
+
+        let t = ar.wrapping_add(f(4 - round, br, cr, dr)).wrapping_add(w[R_RIGHT[j]]).wrapping_add(K_RIGHT[round])
+            .rotate_left(S_RIGHT[j]).wrapping_add(er);
+        ar = er;
+        er = dr;
+        dr = cr.rotate_left(10);
+        cr = br;
+        br = t;
+        j += 1;
+    }
+
+    [
+        state[1].wrapping_add(cl).wrapping_add(dr),
+        state[2].wrapping_add(dl).wrapping_add(er),
+        state[3].wrapping_add(el).wrapping_add(ar),
+        state[4].wrapping_add(al).wrapping_add(br),
+        state[0].wrapping_add(bl).wrapping_add(cr),
+    ]
+}
+
+/// Computes the RIPEMD160 hash of `data`, in a const context.
+#[cfg(rust_v_1_46)]
+const fn digest_const(data: &[u8]) -> [u8; 20] {
+    // The data, the 0x80 byte and the 8-byte length, padded to whole blocks.
+    let n_blocks = (data.len() + 9 + BLOCK_SIZE - 1) / BLOCK_SIZE;
+    let mut state = IV;
+    let mut n = 0;
+    while n < n_blocks {
+        let mut block = [0; BLOCK_SIZE];
+        let mut i = 0;
+        while i < BLOCK_SIZE {
+            let pos = n * BLOCK_SIZE + i;
+            if pos < data.len() {
+                block[i] = data[pos];
+            } else if pos == data.len() {
+                block[i] = 0x80;
+            }
+            i += 1;
+        }
+        if n + 1 == n_blocks {
+            let bits = data.len() as u64 * 8;
+            let mut i = 0;
+            while i < 8 {
+                block[BLOCK_SIZE - 8 + i] = (bits >> (8 * i)) as u8;
+                i += 1;
+            }
+        }
+        state = compress_const(state, &block);
+        n += 1;
+    }
+    state_to_bytes(state)
+}
+
+/// Computes the RIPEMD160 hash of a 32-byte `data`, e.g. a SHA256 hash, in a const context.
+///
+/// See [`sha256::hash_32_const`](crate::sha256::hash_32_const) for why this is not
+/// [`digest_const`].
+#[cfg(rust_v_1_46)]
+pub(crate) const fn hash_32_const(data: &[u8; 32]) -> [u8; 20] {
+    let mut block = [0; BLOCK_SIZE];
+    let mut i = 0;
+    while i < 32 {
+        block[i] = data[i];
+        i += 1;
+    }
+    block[32] = 0x80;
+    // Length of 256 bits
+    block[BLOCK_SIZE - 7] = 0x01;
+    state_to_bytes(compress_const(IV, &block))
+}
+
+/// Serializes a RIPEMD160 state into its little-endian bytes.
+#[cfg(rust_v_1_46)]
+const fn state_to_bytes(state: [u32; 5]) -> [u8; 20] {
+    let mut ret = [0; 20];
+    let mut i = 0;
+    while i < 20 {
+        ret[i] = (state[i / 4] >> (8 * (i % 4))) as u8;
+        i += 1;
+    }
+    ret
+}
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    #[cfg(rust_v_1_46)]
+    fn const_hash() {
+        use crate::{Hash, ripemd160};
+
+        const HASH: ripemd160::Hash = ripemd160::Hash::const_hash(b"abc");
+        assert_eq!(HASH, ripemd160::Hash::hash(b"abc"));
+
+        // One to four blocks, around the padding boundaries
+        let data = [0xa5; 200];
+        for len in 0..data.len() {
+            let bytes = &data[..len];
+            assert_eq!(ripemd160::Hash::const_hash(bytes), ripemd160::Hash::hash(bytes), "length {}", len);
+        }
+        assert_eq!(ripemd160::hash_32_const(&[0xa5; 32]), ripemd160::Hash::hash(&[0xa5; 32]).into_inner());
+    }
+
     #[test]
     #[cfg(any(feature = "std", feature = "alloc"))]
     fn test() {
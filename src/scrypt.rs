@@ -0,0 +1,336 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! scrypt key derivation function.
+//!
+//! Implementation of scrypt from RFC 7914, on top of [`pbkdf2`](crate::pbkdf2) with
+//! HMAC-SHA256. BIP38 encrypts private keys with N = 16384, r = 8, p = 8; Litecoin and its
+//! forks hash block headers with N = 1024, r = 1, p = 1, for which [`derive_1024_1_1`] needs
+//! no allocation.
+//!
+
+use core::fmt;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+use crate::alloc::vec;
+use crate::{pbkdf2, sha256};
+
+/// Size of a Salsa20 block, in 32-bit words.
+const SALSA_WORDS: usize = 16;
+
+/// Error returned for scrypt parameters outside of the ranges allowed by RFC 7914.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct InvalidParamsError;
+
+impl fmt::Display for InvalidParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid scrypt parameters")
+    }
+}
+
+/// Cost parameters of scrypt.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Params {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl Params {
+    /// Creates parameters with CPU/memory cost `N = 2^log_n`, block size `r` and
+    /// parallelization `p`.
+    ///
+    /// # Errors
+    ///
+    /// If `log_n` is zero or not less than `16 * r`, if `r` or `p` is zero, if `r * p` is not
+    /// less than 2^30, or if the memory needed is not addressable on this platform.
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Params, InvalidParamsError> {
+        if log_n == 0 || log_n >= 64 || u64::from(log_n) >= 16 * u64::from(r) || p == 0 {
+            return Err(InvalidParamsError);
+        }
+        if u64::from(r) * u64::from(p) >= 1 << 30 {
+            return Err(InvalidParamsError);
+        }
+        let params = Params { log_n, r, p };
+        if params.sizes().is_none() {
+            return Err(InvalidParamsError);
+        }
+        Ok(params)
+    }
+
+    /// Returns the lengths in 32-bit words of the `p` blocks of `B` and of the `N` blocks of `V`,
+    /// or `None` if either does not fit in memory.
+    fn sizes(&self) -> Option<(usize, usize)> {
+        let block_words = (self.r as usize).checked_mul(32)?;
+        let b_words = block_words.checked_mul(self.p as usize)?;
+        let v_words = block_words.checked_mul(1usize.checked_shl(u32::from(self.log_n))?)?;
+        // Both arrays must also be addressable in bytes.
+        b_words.checked_mul(4)?;
+        v_words.checked_mul(4)?;
+        Some((b_words, v_words))
+    }
+
+    /// Returns the base 2 logarithm of the CPU/memory cost `N`.
+    pub fn log_n(&self) -> u8 {
+        self.log_n
+    }
+
+    /// Returns the block size `r`.
+    pub fn r(&self) -> u32 {
+        self.r
+    }
+
+    /// Returns the parallelization parameter `p`.
+    pub fn p(&self) -> u32 {
+        self.p
+    }
+}
+
+/// The Salsa20/8 core.
+fn salsa20_8(block: &mut [u32]) {
+    fn quarter_round(x: &mut [u32; SALSA_WORDS], a: usize, b: usize, c: usize, d: usize) {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    }
+
+    let mut x = [0; SALSA_WORDS];
+    x.copy_from_slice(block);
+    for _ in 0..4 {
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 5, 9, 13, 1);
+        quarter_round(&mut x, 10, 14, 2, 6);
+        quarter_round(&mut x, 15, 3, 7, 11);
+
+        quarter_round(&mut x, 0, 1, 2, 3);
+        quarter_round(&mut x, 5, 6, 7, 4);
+        quarter_round(&mut x, 10, 11, 8, 9);
+        quarter_round(&mut x, 15, 12, 13, 14);
+    }
+    for (word, mixed) in block.iter_mut().zip(x.iter()) {
+        *word = word.wrapping_add(*mixed);
+    }
+}
+
+/// scryptBlockMix of the `2 * r` Salsa20 blocks of `input` into `output`.
+fn block_mix(input: &[u32], output: &mut [u32]) {
+    let n_blocks = input.len() / SALSA_WORDS;
+    let mut x = [0; SALSA_WORDS];
+    x.copy_from_slice(&input[input.len() - SALSA_WORDS..]);
+
+    for (i, block) in input.chunks(SALSA_WORDS).enumerate() {
+        for (x, word) in x.iter_mut().zip(block) {
+            *x ^= *word;
+        }
+        salsa20_8(&mut x);
+        // Even blocks go to the first half of the output, odd ones to the second half.
+        let out_block = i / 2 + (i % 2) * (n_blocks / 2);
+        output[out_block * SALSA_WORDS..(out_block + 1) * SALSA_WORDS].copy_from_slice(&x);
+    }
+}
+
+/// scryptROMix of `block`, with `v` as scratch space for the `N` intermediate blocks and `y`
+/// as scratch space for one block.
+fn ro_mix(block: &mut [u32], v: &mut [u32], y: &mut [u32]) {
+    let block_len = block.len();
+    let n = v.len() / block_len;
+
+    for chunk in v.chunks_mut(block_len) {
+        chunk.copy_from_slice(block);
+        block_mix(chunk, y);
+        block.copy_from_slice(y);
+    }
+    for _ in 0..n {
+        // Integerify: the first 64 bits of the last Salsa20 block, reduced modulo N
+        let last = block_len - SALSA_WORDS;
+        let int = u64::from(block[last]) | u64::from(block[last + 1]) << 32;
+        let j = (int & (n as u64 - 1)) as usize;
+
+        for (word, other) in block.iter_mut().zip(&v[j * block_len..(j + 1) * block_len]) {
+            *word ^= *other;
+        }
+        block_mix(block, y);
+        block.copy_from_slice(y);
+    }
+}
+
+/// Runs ROMix on every block of `b` and derives the key from the result.
+fn finish(password: &[u8], b: &mut [u8], v: &mut [u32], x: &mut [u32], y: &mut [u32], out: &mut [u8]) {
+    for chunk in b.chunks_mut(x.len() * 4) {
+        for (word, bytes) in x.iter_mut().zip(chunk.chunks(4)) {
+            let mut le = [0; 4];
+            le.copy_from_slice(bytes);
+            *word = u32::from_le_bytes(le);
+        }
+        ro_mix(x, v, y);
+        for (word, bytes) in x.iter().zip(chunk.chunks_mut(4)) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+    pbkdf2::derive::<sha256::Hash>(password, b, 1, out);
+}
+
+/// Fills `out` with the key derived from `password` and `salt` with the cost parameters
+/// `params`.
+///
+/// This allocates `128 * r * (N + p + 1)` bytes.
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "std", feature = "alloc"))))]
+pub fn derive(password: &[u8], salt: &[u8], params: &Params, out: &mut [u8]) {
+    let (b_words, v_words) = params.sizes().expect("sizes checked in Params::new");
+    let block_words = 32 * params.r as usize;
+    let mut b = vec![0; 4 * b_words];
+    pbkdf2::derive::<sha256::Hash>(password, salt, 1, &mut b);
+
+    let mut v = vec![0; v_words];
+    let mut x = vec![0; block_words];
+    let mut y = vec![0; block_words];
+    finish(password, &mut b, &mut v, &mut x, &mut y, out);
+}
+
+/// Fills `out` with the key derived from `password` and `salt` with N = 1024, r = 1, p = 1,
+/// without allocating.
+///
+/// The 128 KiB of scratch space are on the stack. The proof of work of a Litecoin block header
+/// is the 32-byte key derived with the header as both password and salt.
+pub fn derive_1024_1_1(password: &[u8], salt: &[u8], out: &mut [u8]) {
+    let mut b = [0; 128];
+    pbkdf2::derive::<sha256::Hash>(password, salt, 1, &mut b);
+
+    let mut v = [0; 32 * 1024];
+    let mut x = [0; 32];
+    let mut y = [0; 32];
+    finish(password, &mut b, &mut v, &mut x, &mut y, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::hex::ToHex;
+
+        struct Test {
+            password: &'static [u8],
+            salt: &'static [u8],
+            log_n: u8,
+            r: u32,
+            p: u32,
+            output: &'static str,
+        }
+
+        // Test vectors from RFC 7914, except the one needing 1 GiB of memory
+        let tests = [
+            Test {
+                password: b"",
+                salt: b"",
+                log_n: 4,
+                r: 1,
+                p: 1,
+                output: "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442\
+                         fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+            },
+            Test {
+                password: b"password",
+                salt: b"NaCl",
+                log_n: 10,
+                r: 8,
+                p: 16,
+                output: "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+                         2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640",
+            },
+            Test {
+                password: b"pleaseletmein",
+                salt: b"SodiumChloride",
+                log_n: 14,
+                r: 8,
+                p: 1,
+                output: "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2\
+                         d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887",
+            },
+        ];
+        for test in tests.iter() {
+            let mut out = [0; 64];
+            derive(test.password, test.salt, &Params::new(test.log_n, test.r, test.p).unwrap(), &mut out);
+            assert_eq!(out.to_hex(), test.output);
+        }
+
+        // Output of several SHA256 blocks, checked against Python's hashlib
+        let mut out = [0; 100];
+        derive(b"pw", b"salt", &Params::new(2, 2, 3).unwrap(), &mut out);
+        assert_eq!(
+            out.to_hex(),
+            "0ef4708ae1491078c20a9956aa57556b9f0f3b86c910326649b0289406fa6d29ccde1e411164748158a7a5c4\
+             20d2eb551f2976076f27094be6778e270445e8809caa9c1a418f102b0a10db7a23682077bbfcb0ee4420500f\
+             0fad288d9d8af2b77482c270",
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn litecoin_genesis() {
+        use crate::hex::{FromHex, ToHex};
+        use crate::{sha256d, Hash};
+
+        let header = Vec::from_hex(
+            "010000000000000000000000000000000000000000000000000000000000000000000000d9ced4ed1130f7b7\
+             faad9be25323ffafa33232a17c3edf6cfd97bee6bafbdd97b9aa8e4ef0ff0f1ecd513f7c"
+        ).unwrap();
+        assert_eq!(
+            sha256d::Hash::hash(&header).to_string(),
+            "12a765e31ffd4059bada1e25190f6e98c99d9714d334efa41a195a7e7e04bfe2",
+        );
+
+        let mut pow = [0; 32];
+        derive_1024_1_1(&header, &header, &mut pow);
+        pow.reverse();
+        assert_eq!(pow.to_hex(), "0000050c34a64b415b6b15b37f2216634b5b1669cb9a2e38d76f7213b0671e00");
+
+        let mut generic = [0; 32];
+        derive(&header, &header, &Params::new(10, 1, 1).unwrap(), &mut generic);
+        generic.reverse();
+        assert_eq!(generic, pow);
+    }
+
+    #[test]
+    fn params() {
+        assert!(Params::new(10, 1, 1).is_ok());
+        assert!(Params::new(15, 1, 1).is_ok());
+        assert_eq!(Params::new(16, 1, 1), Err(InvalidParamsError));
+        assert_eq!(Params::new(0, 1, 1), Err(InvalidParamsError));
+        assert_eq!(Params::new(10, 0, 1), Err(InvalidParamsError));
+        assert_eq!(Params::new(10, 1, 0), Err(InvalidParamsError));
+        assert_eq!(Params::new(10, 1 << 15, 1 << 15), Err(InvalidParamsError));
+        assert!(Params::new(10, (1 << 15) - 1, 1 << 15).is_ok());
+        // 128 * r * N bytes do not fit in memory
+        assert_eq!(Params::new(60, 4, 1), Err(InvalidParamsError));
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    #[bench]
+    pub fn scrypt_1024_1_1(bh: &mut Bencher) {
+        let header = [0; 80];
+        let mut out = [0; 32];
+        bh.iter( || {
+            super::derive_1024_1_1(&header, &header, &mut out);
+        });
+    }
+}
@@ -19,7 +19,7 @@
 #[cfg(feature = "serde")]
 #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
 pub mod serde_details {
-    use crate::Error;
+    use crate::{hex, sha256, Error};
 
     use core::marker::PhantomData;
     use core::{fmt, ops, str};
@@ -118,6 +118,59 @@ pub mod serde_details {
             }
         }
     }
+
+    /// Midstates of the hash engines usable with HMAC, which
+    /// [`HmacMidState`](crate::hmac::HmacMidState) serializes as their bytes.
+    pub trait MidStateBytes: Sized {
+        /// Returns the bytes of the midstate.
+        fn midstate_bytes(&self) -> &[u8];
+
+        /// Parses a midstate from the bytes returned by [`MidStateBytes::midstate_bytes`].
+        fn from_byte_iter<I>(iter: I) -> Result<Self, hex::Error>
+        where
+            I: Iterator<Item = Result<u8, hex::Error>> + ExactSizeIterator;
+    }
+
+    macro_rules! impl_midstate_bytes_array {
+        ($($len:expr),*) => {
+            $(
+                impl MidStateBytes for [u8; $len] {
+                    fn midstate_bytes(&self) -> &[u8] {
+                        &self[..]
+                    }
+
+                    fn from_byte_iter<I>(iter: I) -> Result<Self, hex::Error>
+                    where
+                        I: Iterator<Item = Result<u8, hex::Error>> + ExactSizeIterator,
+                    {
+                        if iter.len() != $len {
+                            return Err(hex::Error::InvalidLength(2 * $len, 2 * iter.len()));
+                        }
+                        let mut ret = [0; $len];
+                        for (byte, value) in ret.iter_mut().zip(iter) {
+                            *byte = value?;
+                        }
+                        Ok(ret)
+                    }
+                }
+            )*
+        }
+    }
+
+    impl_midstate_bytes_array!(20, 32, 64, 200);
+
+    impl MidStateBytes for sha256::Midstate {
+        fn midstate_bytes(&self) -> &[u8] {
+            &self[..]
+        }
+
+        fn from_byte_iter<I>(iter: I) -> Result<Self, hex::Error>
+        where
+            I: Iterator<Item = Result<u8, hex::Error>> + ExactSizeIterator,
+        {
+            Ok(sha256::Midstate::from_inner(MidStateBytes::from_byte_iter(iter)?))
+        }
+    }
 }
 
 /// Implements `Serialize` and `Deserialize` for a type `$t` which
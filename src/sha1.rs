@@ -22,6 +22,9 @@ use core::slice::SliceIndex;
 
 use crate::{Error, HashEngine as _, hex};
 
+#[cfg(target_arch = "x86_64")]
+mod x86;
+
 crate::internal_macros::hash_type! {
     160,
     false,
@@ -97,51 +100,143 @@ impl crate::HashEngine for HashEngine {
 }
 
 impl HashEngine {
-    // Basic unoptimized algorithm from Wikipedia
     fn process_block(&mut self) {
-        debug_assert_eq!(self.buffer.len(), BLOCK_SIZE);
+        compress_block(&mut self.h, &self.buffer);
+    }
+}
 
-        let mut w = [0u32; 80];
-        for (w_val, buff_bytes) in w.iter_mut().zip(self.buffer.chunks_exact(4)) {
-            *w_val = u32::from_be_bytes(buff_bytes.try_into().expect("4 bytes slice"))
-        }
-        for i in 16..80 {
-            w[i] =(w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+/// Compresses `block` into `state` with the implementation used by [`HashEngine`], which is
+/// hardware-accelerated where supported.
+pub(crate) fn compress_block(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if x86::is_supported() {
+            // SAFETY: the CPU supports the required instructions.
+            unsafe { x86::compress_block(state, block) };
+            return;
         }
+    }
+    compress_block_portable(state, block);
+}
 
-        let mut a = self.h[0];
-        let mut b = self.h[1];
-        let mut c = self.h[2];
-        let mut d = self.h[3];
-        let mut e = self.h[4];
-
-        for (i, &wi) in w.iter().enumerate() {
-            let (f, k) = match i {
-                 0...19 => ((b & c) | (!b & d), 0x5a827999),
-                20...39 => (b ^ c ^ d, 0x6ed9eba1),
-                40...59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
-                60...79 => (b ^ c ^ d, 0xca62c1d6),
-                _ => unreachable!()
-            };
-
-            let new_a = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(wi);
-            e = d;
-            d = c;
-            c = b.rotate_left(30);
-            b = a;
-            a = new_a;
-        }
+/// Compresses `block` into `state` with the portable implementation.
+///
+/// This is what [`HashEngine`] uses where there is no hardware acceleration.
+pub(crate) fn compress_block_portable(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+    // Basic unoptimized algorithm from Wikipedia
+    let mut w = [0u32; 80];
+    for (w_val, buff_bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w_val = u32::from_be_bytes(buff_bytes.try_into().expect("4 bytes slice"))
+    }
+    for i in 16..80 {
+        w[i] =(w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let mut a = state[0];
+    let mut b = state[1];
+    let mut c = state[2];
+    let mut d = state[3];
+    let mut e = state[4];
+
+    for (i, &wi) in w.iter().enumerate() {
+        let (f, k) = match i {
+             0...19 => ((b & c) | (!b & d), 0x5a827999),
+            20...39 => (b ^ c ^ d, 0x6ed9eba1),
+            40...59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            60...79 => (b ^ c ^ d, 0xca62c1d6),
+            _ => unreachable!()
+        };
+
+        let new_a = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(wi);
+        e = d;
+        d = c;
+        c = b.rotate_left(30);
+        b = a;
+        a = new_a;
+    }
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+    state[4] = state[4].wrapping_add(e);
+}
+
+/// The compression functions, exposed to fuzz the accelerated implementations against the
+/// portable one.
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod fuzz {
+    use super::BLOCK_SIZE;
 
-        self.h[0] = self.h[0].wrapping_add(a);
-        self.h[1] = self.h[1].wrapping_add(b);
-        self.h[2] = self.h[2].wrapping_add(c);
-        self.h[3] = self.h[3].wrapping_add(d);
-        self.h[4] = self.h[4].wrapping_add(e);
+    /// Compresses `block` into `state` like [`HashEngine`](super::HashEngine).
+    pub fn compress_block(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+        super::compress_block(state, block)
+    }
+
+    /// Compresses `block` into `state` with the portable implementation.
+    pub fn compress_block_portable(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+        super::compress_block_portable(state, block)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn compress_block_portable() {
+        use crate::{Hash, HashEngine, sha1};
+
+        // Hashes `data` with the portable compression function only.
+        fn portable_hash(data: &[u8]) -> [u8; 20] {
+            let mut padded = [0u8; 6 * 64];
+            padded[..data.len()].copy_from_slice(data);
+            padded[data.len()] = 0x80;
+            let n_blocks = (data.len() + 9 + 63) / 64;
+            padded[n_blocks * 64 - 8..n_blocks * 64].copy_from_slice(&(8 * data.len() as u64).to_be_bytes());
+
+            let mut state = sha1::HashEngine::default().h;
+            for block in padded[..n_blocks * 64].chunks_exact(64) {
+                let mut buf = [0u8; 64];
+                buf.copy_from_slice(block);
+                sha1::compress_block_portable(&mut state, &buf);
+            }
+            let mut ret = [0u8; 20];
+            for (bytes, word) in ret.chunks_exact_mut(4).zip(state.iter()) {
+                bytes.copy_from_slice(&word.to_be_bytes());
+            }
+            ret
+        }
+
+        // Every length up to four blocks, including all the padding boundaries
+        let mut data = [0u8; 256];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i * 7 + 3) as u8;
+        }
+        for len in 0..=data.len() {
+            let expected = portable_hash(&data[..len]);
+            assert_eq!(sha1::Hash::hash(&data[..len]).into_inner(), expected, "length {}", len);
+
+            // Also when the input straddles a block boundary
+            let mut engine = sha1::Hash::engine();
+            engine.input(&data[..len / 2]);
+            engine.input(&data[len / 2..len]);
+            assert_eq!(sha1::Hash::from_engine(engine).into_inner(), expected, "length {}", len);
+        }
+
+        // Chained so that every compression starts from a different state
+        let mut state = [0u32; 5];
+        let mut portable_state = state;
+        for i in 0..1000u32 {
+            let mut block = [(i % 256) as u8; 64];
+            for (chunk, word) in block.chunks_exact_mut(4).zip(state.iter()) {
+                chunk.copy_from_slice(&word.wrapping_mul(i).to_le_bytes());
+            }
+            sha1::compress_block(&mut state, &block);
+            sha1::compress_block_portable(&mut portable_state, &block);
+            assert_eq!(state, portable_state, "block {}", i);
+        }
+    }
+
     #[test]
     #[cfg(any(feature = "std", feature = "alloc"))]
     fn test() {
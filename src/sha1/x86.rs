@@ -0,0 +1,111 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! SHA1 compression with the x86 SHA extensions (SHA-NI).
+//!
+
+use core::arch::x86_64::*;
+
+pub(super) use crate::sha256::x86::is_supported;
+
+use super::BLOCK_SIZE;
+
+/// Four rounds with round function `$func`, updating `$h1` from the state in `$h0`.
+///
+/// The round function must be an immediate, hence the unrolled rounds.
+macro_rules! rounds4 {
+    ($h0:ident, $h1:ident, $w:expr, $func:expr) => {
+        $h1 = _mm_sha1rnds4_epu32($h0, _mm_sha1nexte_epu32($h1, $w), $func);
+    };
+}
+
+/// Replaces `$w0` with the next four words of the message schedule.
+macro_rules! schedule {
+    ($w0:ident, $w1:ident, $w2:ident, $w3:ident) => {
+        $w0 = _mm_sha1msg2_epu32(_mm_xor_si128(_mm_sha1msg1_epu32($w0, $w1), $w2), $w3);
+    };
+}
+
+/// Compresses `block` into `state`.
+///
+/// # Safety
+///
+/// The CPU must support the instructions checked by [`is_supported`].
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+pub(super) unsafe fn compress_block(state: &mut [u32; 5], block: &[u8; BLOCK_SIZE]) {
+    // Reverses the bytes, the message words being big-endian and the first one in the high lane.
+    let mask = _mm_set_epi64x(0x0001_0203_0405_0607, 0x0809_0a0b_0c0d_0e0f);
+
+    let abcd = _mm_set_epi32(state[0] as i32, state[1] as i32, state[2] as i32, state[3] as i32);
+    let e = _mm_set_epi32(state[4] as i32, 0, 0, 0);
+
+    let mut w0 = _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr() as *const __m128i), mask);
+    let mut w1 = _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr().add(16) as *const __m128i), mask);
+    let mut w2 = _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr().add(32) as *const __m128i), mask);
+    let mut w3 = _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr().add(48) as *const __m128i), mask);
+
+    // Rounds 0 to 19, the first ones adding E directly.
+    let mut h0 = abcd;
+    let mut h1 = _mm_sha1rnds4_epu32(h0, _mm_add_epi32(e, w0), 0);
+    rounds4!(h1, h0, w1, 0);
+    rounds4!(h0, h1, w2, 0);
+    rounds4!(h1, h0, w3, 0);
+    schedule!(w0, w1, w2, w3);
+    rounds4!(h0, h1, w0, 0);
+
+    // Rounds 20 to 39
+    schedule!(w1, w2, w3, w0);
+    rounds4!(h1, h0, w1, 1);
+    schedule!(w2, w3, w0, w1);
+    rounds4!(h0, h1, w2, 1);
+    schedule!(w3, w0, w1, w2);
+    rounds4!(h1, h0, w3, 1);
+    schedule!(w0, w1, w2, w3);
+    rounds4!(h0, h1, w0, 1);
+    schedule!(w1, w2, w3, w0);
+    rounds4!(h1, h0, w1, 1);
+
+    // Rounds 40 to 59
+    schedule!(w2, w3, w0, w1);
+    rounds4!(h0, h1, w2, 2);
+    schedule!(w3, w0, w1, w2);
+    rounds4!(h1, h0, w3, 2);
+    schedule!(w0, w1, w2, w3);
+    rounds4!(h0, h1, w0, 2);
+    schedule!(w1, w2, w3, w0);
+    rounds4!(h1, h0, w1, 2);
+    schedule!(w2, w3, w0, w1);
+    rounds4!(h0, h1, w2, 2);
+
+    // Rounds 60 to 79
+    schedule!(w3, w0, w1, w2);
+    rounds4!(h1, h0, w3, 3);
+    schedule!(w0, w1, w2, w3);
+    rounds4!(h0, h1, w0, 3);
+    schedule!(w1, w2, w3, w0);
+    rounds4!(h1, h0, w1, 3);
+    schedule!(w2, w3, w0, w1);
+    rounds4!(h0, h1, w2, 3);
+    schedule!(w3, w0, w1, w2);
+    rounds4!(h1, h0, w3, 3);
+
+    // E is derived from A four rounds before the end.
+    let abcd = _mm_add_epi32(abcd, h0);
+    let e = _mm_sha1nexte_epu32(h1, e);
+    state[0] = _mm_extract_epi32(abcd, 3) as u32;
+    state[1] = _mm_extract_epi32(abcd, 2) as u32;
+    state[2] = _mm_extract_epi32(abcd, 1) as u32;
+    state[3] = _mm_extract_epi32(abcd, 0) as u32;
+    state[4] = _mm_extract_epi32(e, 3) as u32;
+}
@@ -0,0 +1,238 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! SHA224 implementation.
+//!
+//! SHA224 is SHA256 with a different initial state and the output truncated to 28 bytes.
+//!
+
+use core::str;
+use core::ops::Index;
+use core::slice::SliceIndex;
+
+use crate::{Error, hex, sha256};
+
+crate::internal_macros::hash_type! {
+    224,
+    false,
+    "Output of the SHA224 hash function.",
+    "crate::util::json_hex_string::len_28"
+}
+
+fn from_engine(e: HashEngine) -> Hash {
+    let mut ret = [0; 28];
+    ret.copy_from_slice(&sha256::from_engine(e.0)[..28]);
+    Hash(ret)
+}
+
+/// Engine to compute SHA224 hash function.
+#[derive(Clone)]
+pub struct HashEngine(sha256::HashEngine);
+
+impl Default for HashEngine {
+    fn default() -> Self {
+        HashEngine(sha256::HashEngine::sha224())
+    }
+}
+
+impl HashEngine {
+    /// Create a new [`HashEngine`] from a [`sha256::Midstate`].
+    ///
+    /// # Panics
+    ///
+    /// If `length` is not a multiple of the block size.
+    pub fn from_midstate(midstate: sha256::Midstate, length: usize) -> HashEngine {
+        HashEngine(sha256::HashEngine::from_midstate(midstate, length))
+    }
+}
+
+impl crate::FromMidState for HashEngine {
+    fn from_midstate(midstate: sha256::Midstate, length: usize) -> HashEngine {
+        HashEngine::from_midstate(midstate, length)
+    }
+}
+
+impl crate::HashEngine for HashEngine {
+    type MidState = sha256::Midstate;
+
+    fn midstate(&self) -> sha256::Midstate {
+        self.0.midstate()
+    }
+
+    const BLOCK_SIZE: usize = sha256::BLOCK_SIZE;
+
+    fn n_bytes_hashed(&self) -> usize {
+        self.0.n_bytes_hashed()
+    }
+
+    fn input(&mut self, inp: &[u8]) {
+        self.0.input(inp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Hash, HashEngine, sha224};
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::hex::{FromHex, ToHex};
+
+        #[derive(Clone)]
+        struct Test {
+            input: &'static str,
+            output: Vec<u8>,
+            output_str: &'static str,
+        }
+
+        let tests = vec![
+            // Test vectors from NIST FIPS 180-2
+            Test {
+                input: "",
+                output: vec![
+                    0xd1, 0x4a, 0x02, 0x8c, 0x2a, 0x3a, 0x2b, 0xc9,
+                    0x47, 0x61, 0x02, 0xbb, 0x28, 0x82, 0x34, 0xc4,
+                    0x15, 0xa2, 0xb0, 0x1f, 0x82, 0x8e, 0xa6, 0x2a,
+                    0xc5, 0xb3, 0xe4, 0x2f,
+                ],
+                output_str: "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f",
+            },
+            Test {
+                input: "abc",
+                output: vec![
+                    0x23, 0x09, 0x7d, 0x22, 0x34, 0x05, 0xd8, 0x22,
+                    0x86, 0x42, 0xa4, 0x77, 0xbd, 0xa2, 0x55, 0xb3,
+                    0x2a, 0xad, 0xbc, 0xe4, 0xbd, 0xa0, 0xb3, 0xf7,
+                    0xe3, 0x6c, 0x9d, 0xa7,
+                ],
+                output_str: "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+            },
+            Test {
+                input: "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                output: vec![
+                    0x75, 0x38, 0x8b, 0x16, 0x51, 0x27, 0x76, 0xcc,
+                    0x5d, 0xba, 0x5d, 0xa1, 0xfd, 0x89, 0x01, 0x50,
+                    0xb0, 0xc6, 0x45, 0x5c, 0xb4, 0xf5, 0x8b, 0x19,
+                    0x52, 0x52, 0x25, 0x25,
+                ],
+                output_str: "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525",
+            },
+        ];
+
+        for test in tests {
+            // Hash through high-level API, check hex encoding/decoding
+            let hash = sha224::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, sha224::Hash::from_hex(test.output_str).expect("parse hex"));
+            assert_eq!(&hash[..], &test.output[..]);
+            assert_eq!(&hash.to_hex(), &test.output_str);
+
+            // Hash through engine, checking that we can input byte by byte
+            let mut engine = sha224::Hash::engine();
+            for ch in test.input.as_bytes() {
+                engine.input(&[*ch]);
+            }
+            let manual_hash = sha224::Hash::from_engine(engine);
+            assert_eq!(hash, manual_hash);
+            assert_eq!(hash.into_inner()[..].as_ref(), test.output.as_slice());
+        }
+
+        // NIST long message test: one million repetitions of 'a'
+        let mut engine = sha224::Hash::engine();
+        for _ in 0..1000 {
+            engine.input(&[b'a'; 1000]);
+        }
+        assert_eq!(
+            sha224::Hash::from_engine(engine).to_hex(),
+            "20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67",
+        );
+    }
+
+    #[test]
+    fn engine_with_state() {
+        let mut engine = sha224::Hash::engine();
+        engine.input(&[1; 64]);
+
+        // Initializing an engine with midstate from another engine should result in
+        // both engines producing the same hashes
+        let data_vec = vec![vec![3; 1], vec![4; 63], vec![5; 65], vec![6; 66]];
+        for data in data_vec {
+            let mut engine = engine.clone();
+            let mut midstate_engine =
+                sha224::HashEngine::from_midstate(engine.midstate(), engine.n_bytes_hashed());
+            assert_eq!(engine.midstate(), midstate_engine.midstate());
+            engine.input(&data);
+            midstate_engine.input(&data);
+            assert_eq!(engine.midstate(), midstate_engine.midstate());
+            let hash1 = sha224::Hash::from_engine(engine);
+            let hash2 = sha224::Hash::from_engine(midstate_engine);
+            assert_eq!(hash1, hash2);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sha224_serde() {
+        use serde_test::{Configure, Token, assert_tokens};
+
+        static HASH_BYTES: [u8; 28] = [
+            0x23, 0x09, 0x7d, 0x22, 0x34, 0x05, 0xd8, 0x22,
+            0x86, 0x42, 0xa4, 0x77, 0xbd, 0xa2, 0x55, 0xb3,
+            0x2a, 0xad, 0xbc, 0xe4, 0xbd, 0xa0, 0xb3, 0xf7,
+            0xe3, 0x6c, 0x9d, 0xa7,
+        ];
+
+        let hash = sha224::Hash::from_slice(&HASH_BYTES).expect("right number of bytes");
+        assert_tokens(&hash.compact(), &[Token::BorrowedBytes(&HASH_BYTES[..])]);
+        assert_tokens(&hash.readable(), &[Token::Str("23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7")]);
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use crate::{Hash, HashEngine, sha224};
+
+    #[bench]
+    pub fn sha224_10(bh: &mut Bencher) {
+        let mut engine = sha224::Hash::engine();
+        let bytes = [1u8; 10];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha224_1k(bh: &mut Bencher) {
+        let mut engine = sha224::Hash::engine();
+        let bytes = [1u8; 1024];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha224_64k(bh: &mut Bencher) {
+        let mut engine = sha224::Hash::engine();
+        let bytes = [1u8; 65536];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+}
@@ -22,6 +22,9 @@ use core::slice::SliceIndex;
 
 use crate::{Error, HashEngine as _, hex, sha256d};
 
+#[cfg(target_arch = "x86_64")]
+pub(crate) mod x86;
+
 crate::internal_macros::hash_type! {
     256,
     false,
@@ -30,7 +33,7 @@ crate::internal_macros::hash_type! {
 }
 
 #[cfg(not(fuzzing))]
-fn from_engine(mut e: HashEngine) -> Hash {
+pub(crate) fn from_engine(mut e: HashEngine) -> Hash {
     // pad buffer with a single 1-bit then all 0s, until there are exactly 8 bytes remaining
     let data_len = e.length as u64;
 
@@ -50,7 +53,7 @@ fn from_engine(mut e: HashEngine) -> Hash {
 }
 
 #[cfg(fuzzing)]
-fn from_engine(e: HashEngine) -> Hash {
+pub(crate) fn from_engine(e: HashEngine) -> Hash {
     let mut hash = e.midstate().into_inner();
     if hash == [0; 32] {
         // Assume sha256 is secure and never generate 0-hashes (which represent invalid
@@ -60,7 +63,10 @@ fn from_engine(e: HashEngine) -> Hash {
     Hash(hash)
 }
 
-const BLOCK_SIZE: usize = 64;
+pub(crate) const BLOCK_SIZE: usize = 64;
+
+/// Initial state of SHA256.
+const IV: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
 
 /// Engine to compute SHA256 hash function.
 #[derive(Clone)]
@@ -73,7 +79,7 @@ pub struct HashEngine {
 impl Default for HashEngine {
     fn default() -> Self {
         HashEngine {
-            h: [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19],
+            h: IV,
             length: 0,
             buffer: [0; BLOCK_SIZE],
         }
@@ -108,11 +114,26 @@ impl crate::HashEngine for HashEngine {
     engine_input_impl!();
 }
 
+impl crate::FromMidState for HashEngine {
+    fn from_midstate(midstate: Midstate, length: usize) -> HashEngine {
+        HashEngine::from_midstate(midstate, length)
+    }
+}
+
 impl Hash {
     /// Iterate the sha256 algorithm to turn a sha256 hash into a sha256d hash
     pub fn hash_again(&self) -> sha256d::Hash {
         crate::Hash::from_inner(<Self as crate::Hash>::hash(&self.0).0)
     }
+
+    /// Hashes `bytes` in a const context.
+    ///
+    /// This is much slower than [`Hash::hash`](crate::Hash::hash), which should be used at
+    /// runtime. Only available on Rust 1.46 and later.
+    #[cfg(rust_v_1_46)]
+    pub const fn const_hash(bytes: &[u8]) -> Hash {
+        Hash(hash_const(bytes))
+    }
 }
 
 /// Output of the SHA256 hash function.
@@ -143,6 +164,12 @@ impl Midstate {
     /// Length of the midstate, in bytes.
     const LEN: usize = 32;
 
+    /// The initial state of SHA256, before any data is hashed.
+    pub const INITIAL: Midstate = Midstate([
+        0x6a, 0x09, 0xe6, 0x67, 0xbb, 0x67, 0xae, 0x85, 0x3c, 0x6e, 0xf3, 0x72, 0xa5, 0x4f, 0xf5, 0x3a,
+        0x51, 0x0e, 0x52, 0x7f, 0x9b, 0x05, 0x68, 0x8c, 0x1f, 0x83, 0xd9, 0xab, 0x5b, 0xe0, 0xcd, 0x19,
+    ]);
+
     /// Flag indicating whether user-visible serializations of this hash
     /// should be backward. For some reason Satoshi decided this should be
     /// true for `Sha256dHash`, so here we are.
@@ -168,6 +195,63 @@ impl Midstate {
     pub fn into_inner(self) -> [u8; 32] {
         self.0
     }
+
+    /// Computes the midstate of the BIP340 tagged hashes with tag `tag`, i.e. the state after
+    /// hashing `sha256(tag) || sha256(tag)`.
+    ///
+    /// This is a `const fn` on Rust 1.46 and later.
+    #[cfg(rust_v_1_46)]
+    pub const fn hash_tag(tag: &[u8]) -> Midstate {
+        let tag_hash = state_to_bytes(digest_const(tag));
+        let mut block = [0; BLOCK_SIZE];
+        let mut i = 0;
+        while i < 32 {
+            block[i] = tag_hash[i];
+            block[i + 32] = tag_hash[i];
+            i += 1;
+        }
+        Midstate(state_to_bytes(compress_const(IV, &block)))
+    }
+
+    /// Computes the midstate of the BIP340 tagged hashes with tag `tag`, i.e. the state after
+    /// hashing `sha256(tag) || sha256(tag)`.
+    ///
+    /// This is a `const fn` on Rust 1.46 and later.
+    #[cfg(not(rust_v_1_46))]
+    pub fn hash_tag(tag: &[u8]) -> Midstate {
+        let tag_hash = <Hash as crate::Hash>::hash(tag);
+        let mut engine = HashEngine::default();
+        engine.input(&tag_hash[..]);
+        engine.input(&tag_hash[..]);
+        engine.midstate()
+    }
+
+    /// Runs the SHA256 compression function on `block`, starting from this midstate.
+    ///
+    /// This is a `const fn` on Rust 1.46 and later.
+    #[cfg(rust_v_1_46)]
+    pub const fn compress_block(self, block: &[u8; BLOCK_SIZE]) -> Midstate {
+        let mut state = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            state[i] = ((self.0[4 * i] as u32) << 24)
+                | ((self.0[4 * i + 1] as u32) << 16)
+                | ((self.0[4 * i + 2] as u32) << 8)
+                | (self.0[4 * i + 3] as u32);
+            i += 1;
+        }
+        Midstate(state_to_bytes(compress_const(state, block)))
+    }
+
+    /// Runs the SHA256 compression function on `block`, starting from this midstate.
+    ///
+    /// This is a `const fn` on Rust 1.46 and later.
+    #[cfg(not(rust_v_1_46))]
+    pub fn compress_block(self, block: &[u8; BLOCK_SIZE]) -> Midstate {
+        let mut engine = HashEngine::from_midstate(self, 0);
+        engine.input(block);
+        engine.midstate()
+    }
 }
 
 impl hex::FromHex for Midstate {
@@ -202,6 +286,15 @@ macro_rules! round(
 );
 
 impl HashEngine {
+    /// Constructs a hash engine suitable for use inside the default `sha224::HashEngine`.
+    pub(crate) fn sha224() -> Self {
+        HashEngine {
+            h: [0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4],
+            length: 0,
+            buffer: [0; BLOCK_SIZE],
+        }
+    }
+
     /// Create a new [`HashEngine`] from a [`Midstate`].
     ///
     /// # Panics
@@ -222,101 +315,274 @@ impl HashEngine {
         }
     }
 
-    // Algorithm copied from libsecp256k1
     fn process_block(&mut self) {
-        debug_assert_eq!(self.buffer.len(), BLOCK_SIZE);
+        compress_block(&mut self.h, &self.buffer);
+    }
+}
 
-        let mut w = [0u32; 16];
-        for (w_val, buff_bytes) in w.iter_mut().zip(self.buffer.chunks_exact(4)) {
-            *w_val = u32::from_be_bytes(buff_bytes.try_into().expect("4 byte slice"));
+/// Compresses `block` into `state` with the implementation used by [`HashEngine`], which is
+/// hardware-accelerated where supported.
+pub(crate) fn compress_block(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if x86::is_supported() {
+            // SAFETY: the CPU supports the required instructions.
+            unsafe { x86::compress_block(state, block) };
+            return;
         }
+    }
+    compress_block_portable(state, block);
+}
 
-        let mut a = self.h[0];
-        let mut b = self.h[1];
-        let mut c = self.h[2];
-        let mut d = self.h[3];
-        let mut e = self.h[4];
-        let mut f = self.h[5];
-        let mut g = self.h[6];
-        let mut h = self.h[7];
-
-        round!(a, b, c, d, e, f, g, h, 0x428a2f98, w[0]);
-        round!(h, a, b, c, d, e, f, g, 0x71374491, w[1]);
-        round!(g, h, a, b, c, d, e, f, 0xb5c0fbcf, w[2]);
-        round!(f, g, h, a, b, c, d, e, 0xe9b5dba5, w[3]);
-        round!(e, f, g, h, a, b, c, d, 0x3956c25b, w[4]);
-        round!(d, e, f, g, h, a, b, c, 0x59f111f1, w[5]);
-        round!(c, d, e, f, g, h, a, b, 0x923f82a4, w[6]);
-        round!(b, c, d, e, f, g, h, a, 0xab1c5ed5, w[7]);
-        round!(a, b, c, d, e, f, g, h, 0xd807aa98, w[8]);
-        round!(h, a, b, c, d, e, f, g, 0x12835b01, w[9]);
-        round!(g, h, a, b, c, d, e, f, 0x243185be, w[10]);
-        round!(f, g, h, a, b, c, d, e, 0x550c7dc3, w[11]);
-        round!(e, f, g, h, a, b, c, d, 0x72be5d74, w[12]);
-        round!(d, e, f, g, h, a, b, c, 0x80deb1fe, w[13]);
-        round!(c, d, e, f, g, h, a, b, 0x9bdc06a7, w[14]);
-        round!(b, c, d, e, f, g, h, a, 0xc19bf174, w[15]);
-
-        round!(a, b, c, d, e, f, g, h, 0xe49b69c1, w[0], w[14], w[9], w[1]);
-        round!(h, a, b, c, d, e, f, g, 0xefbe4786, w[1], w[15], w[10], w[2]);
-        round!(g, h, a, b, c, d, e, f, 0x0fc19dc6, w[2], w[0], w[11], w[3]);
-        round!(f, g, h, a, b, c, d, e, 0x240ca1cc, w[3], w[1], w[12], w[4]);
-        round!(e, f, g, h, a, b, c, d, 0x2de92c6f, w[4], w[2], w[13], w[5]);
-        round!(d, e, f, g, h, a, b, c, 0x4a7484aa, w[5], w[3], w[14], w[6]);
-        round!(c, d, e, f, g, h, a, b, 0x5cb0a9dc, w[6], w[4], w[15], w[7]);
-        round!(b, c, d, e, f, g, h, a, 0x76f988da, w[7], w[5], w[0], w[8]);
-        round!(a, b, c, d, e, f, g, h, 0x983e5152, w[8], w[6], w[1], w[9]);
-        round!(h, a, b, c, d, e, f, g, 0xa831c66d, w[9], w[7], w[2], w[10]);
-        round!(g, h, a, b, c, d, e, f, 0xb00327c8, w[10], w[8], w[3], w[11]);
-        round!(f, g, h, a, b, c, d, e, 0xbf597fc7, w[11], w[9], w[4], w[12]);
-        round!(e, f, g, h, a, b, c, d, 0xc6e00bf3, w[12], w[10], w[5], w[13]);
-        round!(d, e, f, g, h, a, b, c, 0xd5a79147, w[13], w[11], w[6], w[14]);
-        round!(c, d, e, f, g, h, a, b, 0x06ca6351, w[14], w[12], w[7], w[15]);
-        round!(b, c, d, e, f, g, h, a, 0x14292967, w[15], w[13], w[8], w[0]);
-
-        round!(a, b, c, d, e, f, g, h, 0x27b70a85, w[0], w[14], w[9], w[1]);
-        round!(h, a, b, c, d, e, f, g, 0x2e1b2138, w[1], w[15], w[10], w[2]);
-        round!(g, h, a, b, c, d, e, f, 0x4d2c6dfc, w[2], w[0], w[11], w[3]);
-        round!(f, g, h, a, b, c, d, e, 0x53380d13, w[3], w[1], w[12], w[4]);
-        round!(e, f, g, h, a, b, c, d, 0x650a7354, w[4], w[2], w[13], w[5]);
-        round!(d, e, f, g, h, a, b, c, 0x766a0abb, w[5], w[3], w[14], w[6]);
-        round!(c, d, e, f, g, h, a, b, 0x81c2c92e, w[6], w[4], w[15], w[7]);
-        round!(b, c, d, e, f, g, h, a, 0x92722c85, w[7], w[5], w[0], w[8]);
-        round!(a, b, c, d, e, f, g, h, 0xa2bfe8a1, w[8], w[6], w[1], w[9]);
-        round!(h, a, b, c, d, e, f, g, 0xa81a664b, w[9], w[7], w[2], w[10]);
-        round!(g, h, a, b, c, d, e, f, 0xc24b8b70, w[10], w[8], w[3], w[11]);
-        round!(f, g, h, a, b, c, d, e, 0xc76c51a3, w[11], w[9], w[4], w[12]);
-        round!(e, f, g, h, a, b, c, d, 0xd192e819, w[12], w[10], w[5], w[13]);
-        round!(d, e, f, g, h, a, b, c, 0xd6990624, w[13], w[11], w[6], w[14]);
-        round!(c, d, e, f, g, h, a, b, 0xf40e3585, w[14], w[12], w[7], w[15]);
-        round!(b, c, d, e, f, g, h, a, 0x106aa070, w[15], w[13], w[8], w[0]);
-
-        round!(a, b, c, d, e, f, g, h, 0x19a4c116, w[0], w[14], w[9], w[1]);
-        round!(h, a, b, c, d, e, f, g, 0x1e376c08, w[1], w[15], w[10], w[2]);
-        round!(g, h, a, b, c, d, e, f, 0x2748774c, w[2], w[0], w[11], w[3]);
-        round!(f, g, h, a, b, c, d, e, 0x34b0bcb5, w[3], w[1], w[12], w[4]);
-        round!(e, f, g, h, a, b, c, d, 0x391c0cb3, w[4], w[2], w[13], w[5]);
-        round!(d, e, f, g, h, a, b, c, 0x4ed8aa4a, w[5], w[3], w[14], w[6]);
-        round!(c, d, e, f, g, h, a, b, 0x5b9cca4f, w[6], w[4], w[15], w[7]);
-        round!(b, c, d, e, f, g, h, a, 0x682e6ff3, w[7], w[5], w[0], w[8]);
-        round!(a, b, c, d, e, f, g, h, 0x748f82ee, w[8], w[6], w[1], w[9]);
-        round!(h, a, b, c, d, e, f, g, 0x78a5636f, w[9], w[7], w[2], w[10]);
-        round!(g, h, a, b, c, d, e, f, 0x84c87814, w[10], w[8], w[3], w[11]);
-        round!(f, g, h, a, b, c, d, e, 0x8cc70208, w[11], w[9], w[4], w[12]);
-        round!(e, f, g, h, a, b, c, d, 0x90befffa, w[12], w[10], w[5], w[13]);
-        round!(d, e, f, g, h, a, b, c, 0xa4506ceb, w[13], w[11], w[6], w[14]);
-        round!(c, d, e, f, g, h, a, b, 0xbef9a3f7, w[14], w[12], w[7], w[15]);
-        round!(b, c, d, e, f, g, h, a, 0xc67178f2, w[15], w[13], w[8], w[0]);
-
-        self.h[0] = self.h[0].wrapping_add(a);
-        self.h[1] = self.h[1].wrapping_add(b);
-        self.h[2] = self.h[2].wrapping_add(c);
-        self.h[3] = self.h[3].wrapping_add(d);
-        self.h[4] = self.h[4].wrapping_add(e);
-        self.h[5] = self.h[5].wrapping_add(f);
-        self.h[6] = self.h[6].wrapping_add(g);
-        self.h[7] = self.h[7].wrapping_add(h);
+/// Compresses `block` into `state` with the portable implementation.
+///
+/// This is what [`HashEngine`] uses where there is no hardware acceleration.
+pub(crate) fn compress_block_portable(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    // Algorithm copied from libsecp256k1
+    let mut w = [0u32; 16];
+    for (w_val, buff_bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w_val = u32::from_be_bytes(buff_bytes.try_into().expect("4 byte slice"));
     }
+
+    let mut a = state[0];
+    let mut b = state[1];
+    let mut c = state[2];
+    let mut d = state[3];
+    let mut e = state[4];
+    let mut f = state[5];
+    let mut g = state[6];
+    let mut h = state[7];
+
+    round!(a, b, c, d, e, f, g, h, 0x428a2f98, w[0]);
+    round!(h, a, b, c, d, e, f, g, 0x71374491, w[1]);
+    round!(g, h, a, b, c, d, e, f, 0xb5c0fbcf, w[2]);
+    round!(f, g, h, a, b, c, d, e, 0xe9b5dba5, w[3]);
+    round!(e, f, g, h, a, b, c, d, 0x3956c25b, w[4]);
+    round!(d, e, f, g, h, a, b, c, 0x59f111f1, w[5]);
+    round!(c, d, e, f, g, h, a, b, 0x923f82a4, w[6]);
+    round!(b, c, d, e, f, g, h, a, 0xab1c5ed5, w[7]);
+    round!(a, b, c, d, e, f, g, h, 0xd807aa98, w[8]);
+    round!(h, a, b, c, d, e, f, g, 0x12835b01, w[9]);
+    round!(g, h, a, b, c, d, e, f, 0x243185be, w[10]);
+    round!(f, g, h, a, b, c, d, e, 0x550c7dc3, w[11]);
+    round!(e, f, g, h, a, b, c, d, 0x72be5d74, w[12]);
+    round!(d, e, f, g, h, a, b, c, 0x80deb1fe, w[13]);
+    round!(c, d, e, f, g, h, a, b, 0x9bdc06a7, w[14]);
+    round!(b, c, d, e, f, g, h, a, 0xc19bf174, w[15]);
+
+    round!(a, b, c, d, e, f, g, h, 0xe49b69c1, w[0], w[14], w[9], w[1]);
+    round!(h, a, b, c, d, e, f, g, 0xefbe4786, w[1], w[15], w[10], w[2]);
+    round!(g, h, a, b, c, d, e, f, 0x0fc19dc6, w[2], w[0], w[11], w[3]);
+    round!(f, g, h, a, b, c, d, e, 0x240ca1cc, w[3], w[1], w[12], w[4]);
+    round!(e, f, g, h, a, b, c, d, 0x2de92c6f, w[4], w[2], w[13], w[5]);
+    round!(d, e, f, g, h, a, b, c, 0x4a7484aa, w[5], w[3], w[14], w[6]);
+    round!(c, d, e, f, g, h, a, b, 0x5cb0a9dc, w[6], w[4], w[15], w[7]);
+    round!(b, c, d, e, f, g, h, a, 0x76f988da, w[7], w[5], w[0], w[8]);
+    round!(a, b, c, d, e, f, g, h, 0x983e5152, w[8], w[6], w[1], w[9]);
+    round!(h, a, b, c, d, e, f, g, 0xa831c66d, w[9], w[7], w[2], w[10]);
+    round!(g, h, a, b, c, d, e, f, 0xb00327c8, w[10], w[8], w[3], w[11]);
+    round!(f, g, h, a, b, c, d, e, 0xbf597fc7, w[11], w[9], w[4], w[12]);
+    round!(e, f, g, h, a, b, c, d, 0xc6e00bf3, w[12], w[10], w[5], w[13]);
+    round!(d, e, f, g, h, a, b, c, 0xd5a79147, w[13], w[11], w[6], w[14]);
+    round!(c, d, e, f, g, h, a, b, 0x06ca6351, w[14], w[12], w[7], w[15]);
+    round!(b, c, d, e, f, g, h, a, 0x14292967, w[15], w[13], w[8], w[0]);
+
+    round!(a, b, c, d, e, f, g, h, 0x27b70a85, w[0], w[14], w[9], w[1]);
+    round!(h, a, b, c, d, e, f, g, 0x2e1b2138, w[1], w[15], w[10], w[2]);
+    round!(g, h, a, b, c, d, e, f, 0x4d2c6dfc, w[2], w[0], w[11], w[3]);
+    round!(f, g, h, a, b, c, d, e, 0x53380d13, w[3], w[1], w[12], w[4]);
+    round!(e, f, g, h, a, b, c, d, 0x650a7354, w[4], w[2], w[13], w[5]);
+    round!(d, e, f, g, h, a, b, c, 0x766a0abb, w[5], w[3], w[14], w[6]);
+    round!(c, d, e, f, g, h, a, b, 0x81c2c92e, w[6], w[4], w[15], w[7]);
+    round!(b, c, d, e, f, g, h, a, 0x92722c85, w[7], w[5], w[0], w[8]);
+    round!(a, b, c, d, e, f, g, h, 0xa2bfe8a1, w[8], w[6], w[1], w[9]);
+    round!(h, a, b, c, d, e, f, g, 0xa81a664b, w[9], w[7], w[2], w[10]);
+    round!(g, h, a, b, c, d, e, f, 0xc24b8b70, w[10], w[8], w[3], w[11]);
+    round!(f, g, h, a, b, c, d, e, 0xc76c51a3, w[11], w[9], w[4], w[12]);
+    round!(e, f, g, h, a, b, c, d, 0xd192e819, w[12], w[10], w[5], w[13]);
+    round!(d, e, f, g, h, a, b, c, 0xd6990624, w[13], w[11], w[6], w[14]);
+    round!(c, d, e, f, g, h, a, b, 0xf40e3585, w[14], w[12], w[7], w[15]);
+    round!(b, c, d, e, f, g, h, a, 0x106aa070, w[15], w[13], w[8], w[0]);
+
+    round!(a, b, c, d, e, f, g, h, 0x19a4c116, w[0], w[14], w[9], w[1]);
+    round!(h, a, b, c, d, e, f, g, 0x1e376c08, w[1], w[15], w[10], w[2]);
+    round!(g, h, a, b, c, d, e, f, 0x2748774c, w[2], w[0], w[11], w[3]);
+    round!(f, g, h, a, b, c, d, e, 0x34b0bcb5, w[3], w[1], w[12], w[4]);
+    round!(e, f, g, h, a, b, c, d, 0x391c0cb3, w[4], w[2], w[13], w[5]);
+    round!(d, e, f, g, h, a, b, c, 0x4ed8aa4a, w[5], w[3], w[14], w[6]);
+    round!(c, d, e, f, g, h, a, b, 0x5b9cca4f, w[6], w[4], w[15], w[7]);
+    round!(b, c, d, e, f, g, h, a, 0x682e6ff3, w[7], w[5], w[0], w[8]);
+    round!(a, b, c, d, e, f, g, h, 0x748f82ee, w[8], w[6], w[1], w[9]);
+    round!(h, a, b, c, d, e, f, g, 0x78a5636f, w[9], w[7], w[2], w[10]);
+    round!(g, h, a, b, c, d, e, f, 0x84c87814, w[10], w[8], w[3], w[11]);
+    round!(f, g, h, a, b, c, d, e, 0x8cc70208, w[11], w[9], w[4], w[12]);
+    round!(e, f, g, h, a, b, c, d, 0x90befffa, w[12], w[10], w[5], w[13]);
+    round!(d, e, f, g, h, a, b, c, 0xa4506ceb, w[13], w[11], w[6], w[14]);
+    round!(c, d, e, f, g, h, a, b, 0xbef9a3f7, w[14], w[12], w[7], w[15]);
+    round!(b, c, d, e, f, g, h, a, 0xc67178f2, w[15], w[13], w[8], w[0]);
+
+    state[0] = state[0].wrapping_add(a);
+    state[1] = state[1].wrapping_add(b);
+    state[2] = state[2].wrapping_add(c);
+    state[3] = state[3].wrapping_add(d);
+    state[4] = state[4].wrapping_add(e);
+    state[5] = state[5].wrapping_add(f);
+    state[6] = state[6].wrapping_add(g);
+    state[7] = state[7].wrapping_add(h);
+}
+
+/// The compression functions, exposed to fuzz the accelerated implementations against the
+/// portable one.
+#[cfg(fuzzing)]
+#[doc(hidden)]
+pub mod fuzz {
+    use super::BLOCK_SIZE;
+
+    /// Compresses `block` into `state` like [`HashEngine`](super::HashEngine).
+    pub fn compress_block(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+        super::compress_block(state, block)
+    }
+
+    /// Compresses `block` into `state` with the portable implementation.
+    pub fn compress_block_portable(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+        super::compress_block_portable(state, block)
+    }
+}
+
+/// Round constants of SHA256.
+#[cfg(any(rust_v_1_46, target_arch = "x86_64"))]
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Compresses `block` into `state`, in a const context.
+///
+/// This is much slower than [`HashEngine::process_block`], which should be used at runtime.
+#[cfg(rust_v_1_46)]
+const fn compress_const(state: [u32; 8], block: &[u8; BLOCK_SIZE]) -> [u32; 8] {
+    let mut w = [0u32; 64];
+    let mut i = 0;
+    while i < 16 {
+        w[i] = ((block[4 * i] as u32) << 24)
+            | ((block[4 * i + 1] as u32) << 16)
+            | ((block[4 * i + 2] as u32) << 8)
+            | (block[4 * i + 3] as u32);
+        i += 1;
+    }
+    while i < 64 {
+        w[i] = sigma1!(w[i - 2]).wrapping_add(w[i - 7]).wrapping_add(sigma0!(w[i - 15])).wrapping_add(w[i - 16]);
+        i += 1;
+    }
+
+    let mut a = state[0];
+    let mut b = state[1];
+    let mut c = state[2];
+    let mut d = state[3];
+    let mut e = state[4];
+    let mut f = state[5];
+    let mut g = state[6];
+    let mut h = state[7];
+    let mut i = 0;
+    while i < 64 {
+        let t1 = h.wrapping_add(Sigma1!(e)).wrapping_add(Ch!(e, f, g)).wrapping_add(K[i]).wrapping_add(w[i]);
+        let t2 = Sigma0!(a).wrapping_add(Maj!(a, b, c));
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+        i += 1;
+    }
+
+    [
+        state[0].wrapping_add(a),
+        state[1].wrapping_add(b),
+        state[2].wrapping_add(c),
+        state[3].wrapping_add(d),
+        state[4].wrapping_add(e),
+        state[5].wrapping_add(f),
+        state[6].wrapping_add(g),
+        state[7].wrapping_add(h),
+    ]
+}
+
+/// Computes the final state of the SHA256 hash of `data`, in a const context.
+#[cfg(rust_v_1_46)]
+const fn digest_const(data: &[u8]) -> [u32; 8] {
+    // The data, the 0x80 byte and the 8-byte length, padded to whole blocks.
+    let n_blocks = (data.len() + 9 + BLOCK_SIZE - 1) / BLOCK_SIZE;
+    let mut state = IV;
+    let mut n = 0;
+    while n < n_blocks {
+        let mut block = [0; BLOCK_SIZE];
+        let mut i = 0;
+        while i < BLOCK_SIZE {
+            let pos = n * BLOCK_SIZE + i;
+            if pos < data.len() {
+                block[i] = data[pos];
+            } else if pos == data.len() {
+                block[i] = 0x80;
+            }
+            i += 1;
+        }
+        if n + 1 == n_blocks {
+            let bits = data.len() as u64 * 8;
+            let mut i = 0;
+            while i < 8 {
+                block[BLOCK_SIZE - 8 + i] = (bits >> (56 - 8 * i)) as u8;
+                i += 1;
+            }
+        }
+        state = compress_const(state, &block);
+        n += 1;
+    }
+    state
+}
+
+/// Computes the SHA256 hash of `data`, in a const context.
+#[cfg(rust_v_1_46)]
+pub(crate) const fn hash_const(data: &[u8]) -> [u8; 32] {
+    state_to_bytes(digest_const(data))
+}
+
+/// Computes the SHA256 hash of a 32-byte `data`, e.g. another hash, in a const context.
+///
+/// Unlike [`digest_const`] this does not need the unsizing of `data`, which const fns only
+/// support from Rust 1.54.
+#[cfg(rust_v_1_46)]
+pub(crate) const fn hash_32_const(data: &[u8; 32]) -> [u8; 32] {
+    let mut block = [0; BLOCK_SIZE];
+    let mut i = 0;
+    while i < 32 {
+        block[i] = data[i];
+        i += 1;
+    }
+    block[32] = 0x80;
+    // Length of 256 bits
+    block[BLOCK_SIZE - 2] = 0x01;
+    state_to_bytes(compress_const(IV, &block))
+}
+
+/// Serializes a SHA256 state into its big-endian bytes.
+#[cfg(rust_v_1_46)]
+const fn state_to_bytes(state: [u32; 8]) -> [u8; 32] {
+    let mut ret = [0; 32];
+    let mut i = 0;
+    while i < 32 {
+        ret[i] = (state[i / 4] >> (24 - 8 * (i % 4))) as u8;
+        i += 1;
+    }
+    ret
 }
 
 #[cfg(test)]
@@ -413,6 +679,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hash_tag() {
+        #[cfg(rust_v_1_46)]
+        const TAPLEAF: sha256::Midstate = sha256::Midstate::hash_tag(b"TapLeaf");
+        #[cfg(rust_v_1_46)]
+        assert_eq!(TAPLEAF, sha256::Midstate::hash_tag(b"TapLeaf"));
+
+        // Tags spanning one to three blocks, around the padding boundaries
+        let data = [0x5a; 140];
+        for len in 0..data.len() {
+            let tag = &data[..len];
+            let tag_hash = sha256::Hash::hash(tag);
+            let mut engine = sha256::Hash::engine();
+            engine.input(&tag_hash[..]);
+            engine.input(&tag_hash[..]);
+            assert_eq!(sha256::Midstate::hash_tag(tag), engine.midstate(), "tag length {}", len);
+        }
+    }
+
+    #[test]
+    #[cfg(rust_v_1_46)]
+    fn const_hash() {
+        const HASH: sha256::Hash = sha256::Hash::const_hash(b"abc");
+        assert_eq!(HASH, sha256::Hash::hash(b"abc"));
+
+        // One to four blocks, around the padding boundaries
+        let data = [0xa5; 200];
+        for len in 0..data.len() {
+            let bytes = &data[..len];
+            assert_eq!(sha256::Hash::const_hash(bytes), sha256::Hash::hash(bytes), "length {}", len);
+        }
+        assert_eq!(sha256::hash_32_const(&[0xa5; 32]), sha256::Hash::hash(&[0xa5; 32]).into_inner());
+    }
+
+    #[test]
+    fn compress_block_portable() {
+        // Chained so that every compression starts from a different state
+        let mut state = [0u32; 8];
+        let mut portable_state = state;
+        for i in 0..1000u32 {
+            let mut block = [(i % 256) as u8; 64];
+            for (chunk, word) in block.chunks_exact_mut(4).zip(state.iter()) {
+                chunk.copy_from_slice(&word.wrapping_mul(i).to_le_bytes());
+            }
+            if i % 100 == 99 {
+                block = [0xff; 64];
+            }
+            sha256::compress_block(&mut state, &block);
+            sha256::compress_block_portable(&mut portable_state, &block);
+            assert_eq!(state, portable_state, "block {}", i);
+        }
+    }
+
+    #[test]
+    fn compress_block() {
+        assert_eq!(sha256::Midstate::INITIAL, sha256::HashEngine::default().midstate());
+
+        let mut engine = sha256::HashEngine::default();
+        let mut midstate = sha256::Midstate::INITIAL;
+        for i in 0..4 {
+            let block = [i as u8; 64];
+            engine.input(&block);
+            midstate = midstate.compress_block(&block);
+            assert_eq!(midstate, engine.midstate());
+        }
+    }
+
     #[test]
     fn engine_with_state() {
         let mut engine = sha256::Hash::engine();
@@ -0,0 +1,104 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! SHA256 compression with the x86 SHA extensions (SHA-NI).
+//!
+
+use core::arch::x86_64::*;
+
+use super::{BLOCK_SIZE, K};
+
+/// Whether the CPU supports the SHA extensions, along with the SSE instructions used with them.
+///
+/// With `std` this is detected at runtime on the first call, otherwise the instructions must be
+/// enabled at compile time, e.g. with `-C target-cpu=native`. Also used by the SHA1
+/// implementation, which needs the same instructions.
+#[cfg(feature = "std")]
+pub(crate) fn is_supported() -> bool {
+    use core::sync::atomic::{AtomicU8, Ordering};
+
+    /// Zero until the first call, then one if supported and two otherwise.
+    static SUPPORTED: AtomicU8 = AtomicU8::new(0);
+
+    match SUPPORTED.load(Ordering::Relaxed) {
+        1 => true,
+        2 => false,
+        _ => {
+            let supported = std::is_x86_feature_detected!("sha")
+                && std::is_x86_feature_detected!("sse2")
+                && std::is_x86_feature_detected!("ssse3")
+                && std::is_x86_feature_detected!("sse4.1");
+            SUPPORTED.store(if supported { 1 } else { 2 }, Ordering::Relaxed);
+            supported
+        }
+    }
+}
+
+/// Whether the CPU supports the SHA extensions, along with the SSE instructions used with them.
+///
+/// With `std` this is detected at runtime, otherwise the instructions must be enabled at
+/// compile time, e.g. with `-C target-cpu=native`. Also used by the SHA1 implementation, which
+/// needs the same instructions.
+#[cfg(not(feature = "std"))]
+pub(crate) fn is_supported() -> bool {
+    cfg!(all(target_feature = "sha", target_feature = "sse2", target_feature = "ssse3", target_feature = "sse4.1"))
+}
+
+/// Compresses `block` into `state`.
+///
+/// # Safety
+///
+/// The CPU must support the instructions checked by [`is_supported`].
+#[target_feature(enable = "sha,sse2,ssse3,sse4.1")]
+pub(super) unsafe fn compress_block(state: &mut [u32; 8], block: &[u8; BLOCK_SIZE]) {
+    // Swaps the bytes of each 32-bit word, the message being big-endian.
+    let mask = _mm_set_epi64x(0x0c0d_0e0f_0809_0a0b, 0x0405_0607_0001_0203);
+
+    // The rounds instruction works on the state split into ABEF and CDGH.
+    let dcba = _mm_loadu_si128(state.as_ptr() as *const __m128i);
+    let hgfe = _mm_loadu_si128(state.as_ptr().add(4) as *const __m128i);
+    let cdab = _mm_shuffle_epi32(dcba, 0xb1);
+    let efgh = _mm_shuffle_epi32(hgfe, 0x1b);
+    let mut abef = _mm_alignr_epi8(cdab, efgh, 8);
+    let mut cdgh = _mm_blend_epi16(efgh, cdab, 0xf0);
+    let (abef_save, cdgh_save) = (abef, cdgh);
+
+    // The last 16 words of the message schedule, four per register.
+    let mut w = [
+        _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr() as *const __m128i), mask),
+        _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr().add(16) as *const __m128i), mask),
+        _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr().add(32) as *const __m128i), mask),
+        _mm_shuffle_epi8(_mm_loadu_si128(block.as_ptr().add(48) as *const __m128i), mask),
+    ];
+    for i in 0..16 {
+        if i >= 4 {
+            let t = _mm_add_epi32(
+                _mm_sha256msg1_epu32(w[i % 4], w[(i + 1) % 4]),
+                _mm_alignr_epi8(w[(i + 3) % 4], w[(i + 2) % 4], 4),
+            );
+            w[i % 4] = _mm_sha256msg2_epu32(t, w[(i + 3) % 4]);
+        }
+        let wk = _mm_add_epi32(w[i % 4], _mm_loadu_si128(K.as_ptr().add(4 * i) as *const __m128i));
+        // Two rounds with the low half of `wk`, then two with its high half.
+        cdgh = _mm_sha256rnds2_epu32(cdgh, abef, wk);
+        abef = _mm_sha256rnds2_epu32(abef, cdgh, _mm_shuffle_epi32(wk, 0x0e));
+    }
+
+    abef = _mm_add_epi32(abef, abef_save);
+    cdgh = _mm_add_epi32(cdgh, cdgh_save);
+    let feba = _mm_shuffle_epi32(abef, 0x1b);
+    let dchg = _mm_shuffle_epi32(cdgh, 0xb1);
+    _mm_storeu_si128(state.as_mut_ptr() as *mut __m128i, _mm_blend_epi16(feba, dchg, 0xf0));
+    _mm_storeu_si128(state.as_mut_ptr().add(4) as *mut __m128i, _mm_alignr_epi8(dchg, feba, 8));
+}
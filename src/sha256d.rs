@@ -30,6 +30,17 @@ crate::internal_macros::hash_type! {
 
 type HashEngine = sha256::HashEngine;
 
+impl Hash {
+    /// Hashes `bytes` in a const context.
+    ///
+    /// This is much slower than [`Hash::hash`](crate::Hash::hash), which should be used at
+    /// runtime. Only available on Rust 1.46 and later.
+    #[cfg(rust_v_1_46)]
+    pub const fn const_hash(bytes: &[u8]) -> Hash {
+        Hash(sha256::hash_32_const(&sha256::hash_const(bytes)))
+    }
+}
+
 fn from_engine(e: sha256::HashEngine) -> Hash {
     use crate::Hash as _;
 
@@ -45,6 +56,21 @@ fn from_engine(e: sha256::HashEngine) -> Hash {
 mod tests {
     use crate::sha256;
 
+    #[test]
+    #[cfg(rust_v_1_46)]
+    fn const_hash() {
+        use crate::{sha256d, Hash};
+
+        const HASH: sha256d::Hash = sha256d::Hash::const_hash(b"abc");
+        assert_eq!(HASH, sha256d::Hash::hash(b"abc"));
+
+        let data = [0xa5; 200];
+        for len in 0..data.len() {
+            let bytes = &data[..len];
+            assert_eq!(sha256d::Hash::const_hash(bytes), sha256d::Hash::hash(bytes), "length {}", len);
+        }
+    }
+
     #[test]
     #[cfg(any(feature = "std", feature = "alloc"))]
     fn test() {
@@ -15,17 +15,24 @@
 //! SHA256t implementation (tagged SHA256).
 //!
 
-use core::{cmp, str};
+use core::{cmp, fmt, hash, str};
 use core::marker::PhantomData;
 use core::ops::Index;
 use core::slice::SliceIndex;
+#[cfg(not(rust_v_1_46))]
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
-use crate::{Error, hex, sha256};
+use crate::{Error, HashEngine as _, hex, sha256};
 
 type HashEngine = sha256::HashEngine;
 
 /// Trait representing a tag that can be used as a context for SHA256t hashes.
 pub trait Tag {
+    /// The tag string, or an empty string for tags only known by their midstate.
+    ///
+    /// Tags are compared by midstate, so this is only informative.
+    const NAME: &'static str = "";
+
     /// Returns a hash engine that is pre-tagged and is ready to be used for the data.
     fn engine() -> sha256::HashEngine;
 }
@@ -41,6 +48,12 @@ pub struct Hash<T: Tag>(
 );
 
 impl<T: Tag> Hash<T> {
+    #[cfg(rust_v_1_61)]
+    const fn internal_new(arr: [u8; 32]) -> Self {
+        Hash(arr, PhantomData)
+    }
+
+    #[cfg(not(rust_v_1_61))]
     fn internal_new(arr: [u8; 32]) -> Self {
         Hash(arr, Default::default())
     }
@@ -91,10 +104,188 @@ fn from_engine<T: Tag>(e: sha256::HashEngine) -> Hash<T> {
     Hash::from_inner(sha256::Hash::from_engine(e).into_inner())
 }
 
+/// A tag only known at runtime.
+///
+/// Two tags are equal if they have the same midstate, which is also the case of a `DynTag`
+/// created from the string of a [`Tag`] and one created from the [`Tag`] itself.
+#[derive(Copy, Clone, Debug)]
+pub struct DynTag<'a> {
+    name: &'a str,
+    midstate: sha256::Midstate,
+}
+
+impl<'a> DynTag<'a> {
+    /// Creates the tag with string `name`, computing its midstate.
+    pub fn new(name: &'a str) -> DynTag<'a> {
+        DynTag { name, midstate: sha256::Midstate::hash_tag(name.as_bytes()) }
+    }
+
+    /// Creates the tag with string `name`, looking its midstate up in a small per-thread cache
+    /// of the last tags used.
+    #[cfg(feature = "std")]
+    pub fn new_cached(name: &'a str) -> DynTag<'a> {
+        use std::cell::RefCell;
+        use std::collections::HashMap;
+
+        /// Number of tags above which the cache is cleared.
+        const CACHE_SIZE: usize = 64;
+
+        thread_local! {
+            static CACHE: RefCell<HashMap<String, sha256::Midstate>> = RefCell::new(HashMap::new());
+        }
+
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(midstate) = cache.get(name) {
+                return DynTag { name, midstate: *midstate };
+            }
+            let tag = DynTag::new(name);
+            if cache.len() >= CACHE_SIZE {
+                cache.clear();
+            }
+            cache.insert(name.to_owned(), tag.midstate);
+            tag
+        })
+    }
+
+    /// Returns the tag string, empty if created from a [`Tag`] without [`Tag::NAME`].
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns the midstate after hashing the tag.
+    pub fn midstate(&self) -> sha256::Midstate {
+        self.midstate
+    }
+
+    /// Returns a hash engine that is pre-tagged and is ready to be used for the data.
+    pub fn engine(&self) -> sha256::HashEngine {
+        sha256::HashEngine::from_midstate(self.midstate, 64)
+    }
+
+    /// Hashes some bytes with this tag.
+    pub fn hash(&self, data: &[u8]) -> sha256::Hash {
+        let mut engine = self.engine();
+        engine.input(data);
+        <sha256::Hash as crate::Hash>::from_engine(engine)
+    }
+
+    /// Checks whether this is the tag `T`.
+    pub fn is<T: Tag>(&self) -> bool {
+        self.midstate == T::engine().midstate()
+    }
+}
+
+impl DynTag<'static> {
+    /// Creates the runtime equivalent of the tag `T`.
+    pub fn from_tag<T: Tag>() -> DynTag<'static> {
+        DynTag { name: T::NAME, midstate: T::engine().midstate() }
+    }
+}
+
+impl<'a> PartialEq for DynTag<'a> {
+    fn eq(&self, other: &DynTag) -> bool {
+        self.midstate == other.midstate
+    }
+}
+
+impl<'a> Eq for DynTag<'a> {}
+
+impl<'a> hash::Hash for DynTag<'a> {
+    fn hash<H: hash::Hasher>(&self, h: &mut H) {
+        self.midstate.hash(h)
+    }
+}
+
+impl<'a> fmt::Display for DynTag<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// Midstate of the tagged hashes with a given tag, for use by [`sha256t_hash_newtype!`].
+///
+/// On Rust 1.46 and later the midstate is computed at compile time. Otherwise it is computed
+/// on first use and cached.
+#[doc(hidden)]
+pub struct TagMidstate {
+    #[cfg(rust_v_1_46)]
+    midstate: sha256::Midstate,
+    #[cfg(not(rust_v_1_46))]
+    tag: &'static str,
+    #[cfg(not(rust_v_1_46))]
+    words: [AtomicU32; 8],
+    #[cfg(not(rust_v_1_46))]
+    initialized: AtomicBool,
+}
+
+impl TagMidstate {
+    /// Creates the midstate of the tagged hashes with tag `tag`.
+    #[cfg(rust_v_1_46)]
+    pub const fn new(tag: &'static str) -> TagMidstate {
+        TagMidstate { midstate: sha256::Midstate::hash_tag(tag.as_bytes()) }
+    }
+
+    /// Creates the midstate of the tagged hashes with tag `tag`.
+    #[cfg(not(rust_v_1_46))]
+    pub const fn new(tag: &'static str) -> TagMidstate {
+        TagMidstate {
+            tag,
+            words: [
+                AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0),
+                AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0),
+            ],
+            initialized: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the midstate.
+    #[cfg(rust_v_1_46)]
+    pub fn midstate(&self) -> sha256::Midstate {
+        self.midstate
+    }
+
+    /// Returns the midstate.
+    #[cfg(not(rust_v_1_46))]
+    pub fn midstate(&self) -> sha256::Midstate {
+        let mut ret = [0; 32];
+        if self.initialized.load(Ordering::Acquire) {
+            for (word, bytes) in self.words.iter().zip(ret.chunks_mut(4)) {
+                bytes.copy_from_slice(&word.load(Ordering::Relaxed).to_be_bytes());
+            }
+            return sha256::Midstate::from_inner(ret);
+        }
+
+        // Racing threads all store the same value.
+        let midstate = sha256::Midstate::hash_tag(self.tag.as_bytes());
+        for (word, bytes) in self.words.iter().zip(midstate[..].chunks(4)) {
+            ret[..4].copy_from_slice(bytes);
+            word.store(u32::from_be_bytes([ret[0], ret[1], ret[2], ret[3]]), Ordering::Relaxed);
+        }
+        self.initialized.store(true, Ordering::Release);
+        midstate
+    }
+
+    /// Returns an engine starting from the midstate.
+    pub fn engine(&self) -> sha256::HashEngine {
+        sha256::HashEngine::from_midstate(self.midstate(), 64)
+    }
+}
+
 /// Macro used to define a newtype tagged hash.
 /// It creates two public types:
 /// - a sha256t::Tag struct,
 /// - a sha256t::Hash type alias.
+///
+/// The tag is given either as a string, whose midstate is computed at compile time on Rust 1.46
+/// and later, or as the identifier of a precomputed midstate followed by the number of bytes it
+/// hashed.
+///
+/// ```
+/// use bitcoin_hashes::sha256t_hash_newtype;
+///
+/// sha256t_hash_newtype!(TapLeafHash, TapLeafTag, "TapLeaf", doc="Tagged hash of a taproot leaf.", false);
+/// ```
 #[macro_export]
 macro_rules! sha256t_hash_newtype {
     ($newtype:ident, $tag:ident, $midstate:ident, $midstate_len:expr, $docs:meta, $reverse: expr) => {
@@ -117,8 +308,33 @@ macro_rules! sha256t_hash_newtype {
 
         $crate::hash_newtype!($newtype, $crate::sha256t::Hash<$tag>, 32, $docs, $reverse);
     };
+
+    ($newtype:ident, $tag:ident, $tag_str:literal, $docs:meta, $reverse: expr) => {
+        sha256t_hash_newtype!($newtype, $tag, $tag_str, $docs, $reverse, stringify!($newtype));
+    };
+
+    ($newtype:ident, $tag:ident, $tag_str:literal, $docs:meta, $reverse: expr, $sname:expr) => {
+        #[doc = "The tag used for ["]
+        #[doc = $sname]
+        #[doc = "]"]
+        #[derive(Copy, Clone, PartialEq, Eq, Default, PartialOrd, Ord, Hash)]
+        pub struct $tag;
+
+        impl $crate::sha256t::Tag for $tag {
+            const NAME: &'static str = $tag_str;
+
+            fn engine() -> $crate::sha256::HashEngine {
+                static MIDSTATE: $crate::sha256t::TagMidstate = $crate::sha256t::TagMidstate::new($tag_str);
+                MIDSTATE.engine()
+            }
+        }
+
+        $crate::hash_newtype!($newtype, $crate::sha256t::Hash<$tag>, 32, $docs, $reverse);
+    };
 }
 
+pub mod tags;
+
 #[cfg(test)]
 mod tests {
     use crate::{sha256, sha256t};
@@ -149,6 +365,7 @@ mod tests {
     pub type TestHash = sha256t::Hash<TestHashTag>;
 
     sha256t_hash_newtype!(NewTypeHash, NewTypeTag, TEST_MIDSTATE, 64, doc="test hash", true);
+    sha256t_hash_newtype!(TagStrHash, TagStrTag, "TapLeaf", doc="test hash", true);
 
     #[test]
     #[cfg(any(feature = "std", feature = "alloc"))]
@@ -161,5 +378,61 @@ mod tests {
             NewTypeHash::hash(&[0]).to_hex(),
             "29589d5122ec666ab5b4695070b6debc63881a4f85d88d93ddc90078038213ed"
         );
+        assert_eq!(
+            TagStrHash::hash(&[0]).to_hex(),
+            "29589d5122ec666ab5b4695070b6debc63881a4f85d88d93ddc90078038213ed"
+        );
+    }
+
+    #[test]
+    fn tag_midstate() {
+        use crate::HashEngine;
+
+        let midstate = sha256t::TagMidstate::new("TapLeaf");
+        assert_eq!(midstate.midstate().into_inner(), TEST_MIDSTATE);
+        // Cached on first use without a const fn
+        assert_eq!(midstate.midstate().into_inner(), TEST_MIDSTATE);
+        assert_eq!(midstate.engine().n_bytes_hashed(), 64);
+        assert_eq!(<TagStrTag as sha256t::Tag>::engine().midstate().into_inner(), TEST_MIDSTATE);
+    }
+
+    #[test]
+    fn dyn_tag() {
+        use crate::Hash;
+        use crate::sha256t::{DynTag, Tag};
+
+        let tag = DynTag::new("TapLeaf");
+        assert_eq!(tag.midstate().into_inner(), TEST_MIDSTATE);
+        assert_eq!(tag.hash(&[0]).into_inner(), TagStrHash::hash(&[0]).into_inner());
+        assert_eq!(tag.name(), "TapLeaf");
+
+        assert_eq!(TagStrTag::NAME, "TapLeaf");
+        assert_eq!(TestHashTag::NAME, "");
+        assert!(tag.is::<TagStrTag>());
+        assert!(tag.is::<TestHashTag>());
+        assert_eq!(DynTag::from_tag::<TagStrTag>(), tag);
+        assert_eq!(DynTag::from_tag::<TestHashTag>(), tag);
+        assert_eq!(DynTag::from_tag::<TestHashTag>().name(), "");
+
+        let other = DynTag::new("TapBranch");
+        assert!(!other.is::<TagStrTag>());
+        assert_ne!(other, tag);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn dyn_tag_cached() {
+        use crate::sha256t::DynTag;
+
+        let tag = DynTag::new("TapLeaf");
+        assert_eq!(DynTag::new_cached("TapLeaf"), tag);
+        assert_eq!(DynTag::new_cached("TapLeaf").midstate(), tag.midstate());
+        assert_eq!(tag.to_string(), "TapLeaf");
+
+        // Filling the cache past its size
+        let names: Vec<String> = (0..100).map(|i| format!("tag {}", i)).collect();
+        for name in names.iter().chain(names.iter()) {
+            assert_eq!(DynTag::new_cached(name), DynTag::new(name));
+        }
     }
 }
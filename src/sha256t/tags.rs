@@ -0,0 +1,145 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! Tags of the BIP340 tagged hashes defined by BIPs.
+//!
+//! Each tag comes with a hash newtype, whose engine starts from the midstate after hashing
+//! `sha256(tag) || sha256(tag)`.
+//!
+
+// BIP340 Schnorr signatures.
+sha256t_hash_newtype!(Bip340ChallengeHash, Bip340ChallengeTag, "BIP0340/challenge",
+    doc="Challenge of a BIP340 signature, tagged `BIP0340/challenge`.", false
+);
+sha256t_hash_newtype!(Bip340AuxHash, Bip340AuxTag, "BIP0340/aux",
+    doc="Hash of the auxiliary randomness of a BIP340 signature, tagged `BIP0340/aux`.", false
+);
+sha256t_hash_newtype!(Bip340NonceHash, Bip340NonceTag, "BIP0340/nonce",
+    doc="Nonce of a BIP340 signature, tagged `BIP0340/nonce`.", false
+);
+
+// BIP341 taproot.
+sha256t_hash_newtype!(TapLeafHash, TapLeafTag, "TapLeaf",
+    doc="Hash of a taproot script tree leaf, tagged `TapLeaf`.", false
+);
+sha256t_hash_newtype!(TapBranchHash, TapBranchTag, "TapBranch",
+    doc="Hash of a node of a taproot script tree, tagged `TapBranch`.", false
+);
+sha256t_hash_newtype!(TapTweakHash, TapTweakTag, "TapTweak",
+    doc="Tweak of a taproot internal key, tagged `TapTweak`.", false
+);
+sha256t_hash_newtype!(TapSighashHash, TapSighashTag, "TapSighash",
+    doc="Taproot signature hash, tagged `TapSighash`.", false
+);
+
+// BIP322 generic signed messages.
+sha256t_hash_newtype!(Bip322MessageHash, Bip322MessageTag, "BIP0322-signed-message",
+    doc="Hash of a BIP322 signed message, tagged `BIP0322-signed-message`.", false
+);
+
+// BIP327 MuSig2.
+sha256t_hash_newtype!(KeyAggListHash, KeyAggListTag, "KeyAgg list",
+    doc="Hash of the public keys of a MuSig2 key aggregation, tagged `KeyAgg list`.", false
+);
+sha256t_hash_newtype!(KeyAggCoefficientHash, KeyAggCoefficientTag, "KeyAgg coefficient",
+    doc="MuSig2 key aggregation coefficient, tagged `KeyAgg coefficient`.", false
+);
+sha256t_hash_newtype!(MusigAuxHash, MusigAuxTag, "MuSig/aux",
+    doc="Hash of the auxiliary randomness of a MuSig2 nonce, tagged `MuSig/aux`.", false
+);
+sha256t_hash_newtype!(MusigNonceHash, MusigNonceTag, "MuSig/nonce",
+    doc="MuSig2 secret nonce, tagged `MuSig/nonce`.", false
+);
+sha256t_hash_newtype!(MusigNonceCoefHash, MusigNonceCoefTag, "MuSig/noncecoef",
+    doc="MuSig2 nonce coefficient, tagged `MuSig/noncecoef`.", false
+);
+
+// BIP330 transaction reconciliation.
+sha256t_hash_newtype!(TxRelaySaltingHash, TxRelaySaltingTag, "Tx Relay Salting",
+    doc="Hash of the salts of a reconciling connection, tagged `Tx Relay Salting`.", false
+);
+
+// BIP352 silent payments.
+sha256t_hash_newtype!(SilentPaymentsInputsHash, SilentPaymentsInputsTag, "BIP0352/Inputs",
+    doc="Hash of the inputs of a silent payment, tagged `BIP0352/Inputs`.", false
+);
+sha256t_hash_newtype!(SilentPaymentsSharedSecretHash, SilentPaymentsSharedSecretTag, "BIP0352/SharedSecret",
+    doc="Silent payment shared secret, tagged `BIP0352/SharedSecret`.", false
+);
+sha256t_hash_newtype!(SilentPaymentsLabelHash, SilentPaymentsLabelTag, "BIP0352/Label",
+    doc="Silent payment label, tagged `BIP0352/Label`.", false
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256t::Tag;
+    use crate::{sha256, Hash, HashEngine};
+
+    fn check<T: Tag>(tag: &str) {
+        let tag_hash = sha256::Hash::hash(tag.as_bytes());
+        let mut expected = sha256::Hash::engine();
+        expected.input(&tag_hash[..]);
+        expected.input(&tag_hash[..]);
+
+        let engine = T::engine();
+        assert_eq!(engine.midstate(), expected.midstate(), "midstate of {}", tag);
+        assert_eq!(engine.n_bytes_hashed(), 64);
+        assert_eq!(T::NAME, tag);
+    }
+
+    #[test]
+    fn midstates() {
+        check::<Bip340ChallengeTag>("BIP0340/challenge");
+        check::<Bip340AuxTag>("BIP0340/aux");
+        check::<Bip340NonceTag>("BIP0340/nonce");
+        check::<TapLeafTag>("TapLeaf");
+        check::<TapBranchTag>("TapBranch");
+        check::<TapTweakTag>("TapTweak");
+        check::<TapSighashTag>("TapSighash");
+        check::<Bip322MessageTag>("BIP0322-signed-message");
+        check::<KeyAggListTag>("KeyAgg list");
+        check::<KeyAggCoefficientTag>("KeyAgg coefficient");
+        check::<MusigAuxTag>("MuSig/aux");
+        check::<MusigNonceTag>("MuSig/nonce");
+        check::<MusigNonceCoefTag>("MuSig/noncecoef");
+        check::<TxRelaySaltingTag>("Tx Relay Salting");
+        check::<SilentPaymentsInputsTag>("BIP0352/Inputs");
+        check::<SilentPaymentsSharedSecretTag>("BIP0352/SharedSecret");
+        check::<SilentPaymentsLabelTag>("BIP0352/Label");
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn tagged_hashes() {
+        use crate::hex::FromHex;
+
+        // From the BIP341 wallet test vectors, tweak of a key without script tree
+        let internal_key = Vec::from_hex("d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d").unwrap();
+        assert_eq!(
+            TapTweakHash::hash(&internal_key).to_string(),
+            "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70",
+        );
+
+        // From the BIP322 test vectors
+        assert_eq!(
+            Bip322MessageHash::hash(b"").to_string(),
+            "c90c269c4f8fcbe6880f72a721ddfbf1914268a794cbb21cfafee13770ae19f1",
+        );
+        assert_eq!(
+            Bip322MessageHash::hash(b"Hello World").to_string(),
+            "f0eb03b1a75ac6d9847f55c624a99169b5dccba2a31f5b23bea77ba270de0a7a",
+        );
+    }
+}
@@ -0,0 +1,537 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! SHA3 (FIPS 202) implementation.
+//!
+//! All hash functions in this module are instances of the Keccak sponge construction over the
+//! Keccak-f\[1600\] permutation, differing only in their rate, domain separation byte and output
+//! length. The fixed-length hashes live in the [`sha3_256`], [`sha3_512`] and [`keccak256`]
+//! submodules; the extendable-output functions SHAKE128 and SHAKE256 are provided by [`Shake128`]
+//! and [`Shake256`], which are finalized into a [`ShakeReader`].
+//!
+
+/// Round constants of the iota step.
+const RC: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+/// Rotation offsets of the rho step, in the order lanes are visited by the pi step.
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+/// Lane indices visited by the pi step, starting from lane 1.
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+/// Size of the Keccak-f\[1600\] state, in bytes.
+pub const STATE_SIZE: usize = 200;
+
+/// Domain separation byte (including the first padding bit) of the SHA3 hash functions.
+const SHA3_DOMAIN: u8 = 0x06;
+/// Domain separation byte of the original Keccak submission, as used by Ethereum.
+const KECCAK_DOMAIN: u8 = 0x01;
+/// Domain separation byte of the SHAKE extendable-output functions.
+const SHAKE_DOMAIN: u8 = 0x1f;
+
+/// Applies the Keccak-f\[1600\] permutation to `state`.
+///
+/// Lane `(x, y)` of the state is stored at index `x + 5 * y`.
+pub fn keccak_f1600(state: &mut [u64; 25]) {
+    for rc in RC.iter() {
+        // theta
+        let mut c = [0u64; 5];
+        for (x, c_x) in c.iter_mut().enumerate() {
+            *c_x = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho and pi
+        let mut last = state[1];
+        for (&pi, &rho) in PI.iter().zip(RHO.iter()) {
+            let tmp = state[pi];
+            state[pi] = last.rotate_left(rho);
+            last = tmp;
+        }
+
+        // chi
+        for y in 0..5 {
+            let mut row = [0u64; 5];
+            row.copy_from_slice(&state[5 * y..5 * y + 5]);
+            for x in 0..5 {
+                state[x + 5 * y] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // iota
+        state[0] ^= *rc;
+    }
+}
+
+/// Keccak sponge with a given rate, shared by all the engines in this module.
+#[derive(Clone)]
+struct Sponge {
+    state: [u64; 25],
+    rate: usize,
+    pos: usize,
+    length: usize,
+}
+
+impl Sponge {
+    fn new(rate: usize) -> Sponge {
+        debug_assert!(rate % 8 == 0 && rate < STATE_SIZE);
+        Sponge {
+            state: [0; 25],
+            rate,
+            pos: 0,
+            length: 0,
+        }
+    }
+
+    #[inline]
+    fn xor_byte(&mut self, idx: usize, byte: u8) {
+        self.state[idx / 8] ^= u64::from(byte) << (8 * (idx % 8));
+    }
+
+    #[inline]
+    fn get_byte(&self, idx: usize) -> u8 {
+        (self.state[idx / 8] >> (8 * (idx % 8))) as u8
+    }
+
+    fn absorb(&mut self, inp: &[u8]) {
+        self.length += inp.len();
+        for byte in inp {
+            self.xor_byte(self.pos, *byte);
+            self.pos += 1;
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Pads the absorbed data using `domain` as separator and switches to squeezing.
+    fn pad(&mut self, domain: u8) {
+        self.xor_byte(self.pos, domain);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_f1600(&mut self.state);
+        self.pos = 0;
+    }
+
+    fn squeeze(&mut self, out: &mut [u8]) {
+        for byte in out {
+            if self.pos == self.rate {
+                keccak_f1600(&mut self.state);
+                self.pos = 0;
+            }
+            *byte = self.get_byte(self.pos);
+            self.pos += 1;
+        }
+    }
+
+    fn midstate(&self) -> [u8; STATE_SIZE] {
+        let mut ret = [0; STATE_SIZE];
+        for (val, ret_bytes) in self.state.iter().zip(ret.chunks_exact_mut(8)) {
+            ret_bytes.copy_from_slice(&val.to_le_bytes());
+        }
+        ret
+    }
+
+    fn finalize_into(mut self, domain: u8, out: &mut [u8]) {
+        self.pad(domain);
+        self.squeeze(out);
+    }
+}
+
+macro_rules! sponge_engine {
+    ($(#[$doc:meta])* $engine:ident, $rate:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $engine(Sponge);
+
+        impl Default for $engine {
+            fn default() -> Self {
+                $engine(Sponge::new($rate))
+            }
+        }
+
+        impl crate::HashEngine for $engine {
+            type MidState = [u8; STATE_SIZE];
+
+            fn midstate(&self) -> [u8; STATE_SIZE] {
+                self.0.midstate()
+            }
+
+            const BLOCK_SIZE: usize = $rate;
+
+            fn n_bytes_hashed(&self) -> usize {
+                self.0.length
+            }
+
+            fn input(&mut self, inp: &[u8]) {
+                self.0.absorb(inp)
+            }
+        }
+    };
+}
+
+sponge_engine!(
+    /// Engine to compute the SHAKE128 extendable-output function.
+    Shake128,
+    168
+);
+
+sponge_engine!(
+    /// Engine to compute the SHAKE256 extendable-output function.
+    Shake256,
+    136
+);
+
+impl Shake128 {
+    /// Finalizes the engine, returning a reader which produces an arbitrary amount of output.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.0.pad(SHAKE_DOMAIN);
+        ShakeReader(self.0)
+    }
+}
+
+impl Shake256 {
+    /// Finalizes the engine, returning a reader which produces an arbitrary amount of output.
+    pub fn finalize_xof(mut self) -> ShakeReader {
+        self.0.pad(SHAKE_DOMAIN);
+        ShakeReader(self.0)
+    }
+}
+
+/// Output stream of a finalized SHAKE engine.
+#[derive(Clone)]
+pub struct ShakeReader(Sponge);
+
+impl ShakeReader {
+    /// Fills `out` with the next `out.len()` bytes of output.
+    ///
+    /// Successive calls continue where the previous one stopped, so the output does not depend
+    /// on how it is split between calls.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        self.0.squeeze(out)
+    }
+}
+
+/// SHA3-256 hash function.
+pub mod sha3_256 {
+    use core::str;
+    use core::ops::Index;
+    use core::slice::SliceIndex;
+
+    use super::{Sponge, SHA3_DOMAIN, STATE_SIZE};
+    use crate::{Error, hex};
+
+    crate::internal_macros::hash_type! {
+        256,
+        false,
+        "Output of the SHA3-256 hash function.",
+        "crate::util::json_hex_string::len_32"
+    }
+
+    fn from_engine(e: HashEngine) -> Hash {
+        let mut ret = [0; 32];
+        e.0.finalize_into(SHA3_DOMAIN, &mut ret);
+        Hash(ret)
+    }
+
+    sponge_engine!(
+        /// Engine to compute the SHA3-256 hash function.
+        HashEngine,
+        136
+    );
+}
+
+/// SHA3-512 hash function.
+pub mod sha3_512 {
+    use core::{cmp, hash, str};
+    use core::ops::Index;
+    use core::slice::SliceIndex;
+
+    use super::{Sponge, SHA3_DOMAIN, STATE_SIZE};
+    use crate::{Error, hex};
+
+    crate::internal_macros::hash_trait_impls!(512, false);
+
+    /// Output of the SHA3-512 hash function.
+    #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+    #[repr(transparent)]
+    pub struct Hash(
+        #[cfg_attr(feature = "schemars", schemars(schema_with = "crate::util::json_hex_string::len_64"))]
+        [u8; 64]
+    );
+
+    impl Hash {
+        const fn internal_new(arr: [u8; 64]) -> Self {
+            Hash(arr)
+        }
+
+        fn internal_engine() -> HashEngine {
+            Default::default()
+        }
+    }
+
+    impl Copy for Hash {}
+
+    impl Clone for Hash {
+        fn clone(&self) -> Hash {
+            *self
+        }
+    }
+
+    impl PartialEq for Hash {
+        fn eq(&self, other: &Hash) -> bool {
+            self.0[..] == other.0[..]
+        }
+    }
+
+    impl Eq for Hash {}
+
+    impl Default for Hash {
+        fn default() -> Hash {
+            Hash([0; 64])
+        }
+    }
+
+    impl PartialOrd for Hash {
+        fn partial_cmp(&self, other: &Hash) -> Option<cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Hash {
+        fn cmp(&self, other: &Hash) -> cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl hash::Hash for Hash {
+        fn hash<H: hash::Hasher>(&self, state: &mut H) {
+            self.0.hash(state)
+        }
+    }
+
+    fn from_engine(e: HashEngine) -> Hash {
+        let mut ret = [0; 64];
+        e.0.finalize_into(SHA3_DOMAIN, &mut ret);
+        Hash(ret)
+    }
+
+    sponge_engine!(
+        /// Engine to compute the SHA3-512 hash function.
+        HashEngine,
+        72
+    );
+}
+
+/// Keccak-256 hash function.
+///
+/// This is the hash function of the original Keccak submission, which differs from the
+/// standardized SHA3-256 only in its padding. It is used by Ethereum and related systems.
+pub mod keccak256 {
+    use core::str;
+    use core::ops::Index;
+    use core::slice::SliceIndex;
+
+    use super::{Sponge, KECCAK_DOMAIN, STATE_SIZE};
+    use crate::{Error, hex};
+
+    crate::internal_macros::hash_type! {
+        256,
+        false,
+        "Output of the Keccak-256 hash function.",
+        "crate::util::json_hex_string::len_32"
+    }
+
+    fn from_engine(e: HashEngine) -> Hash {
+        let mut ret = [0; 32];
+        e.0.finalize_into(KECCAK_DOMAIN, &mut ret);
+        Hash(ret)
+    }
+
+    sponge_engine!(
+        /// Engine to compute the Keccak-256 hash function.
+        HashEngine,
+        136
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Hash, HashEngine};
+    use super::{keccak256, sha3_256, sha3_512, Shake128, Shake256};
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::hex::{FromHex, ToHex};
+
+        struct Test {
+            input: &'static str,
+            sha3_256: &'static str,
+            sha3_512: &'static str,
+            keccak256: &'static str,
+        }
+
+        let tests = vec![
+            // Test vectors from NIST and the Keccak team
+            Test {
+                input: "",
+                sha3_256: "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+                sha3_512: "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a6\
+                           15b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26",
+                keccak256: "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            },
+            Test {
+                input: "abc",
+                sha3_256: "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+                sha3_512: "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e\
+                           10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+                keccak256: "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45",
+            },
+            Test {
+                input: "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                sha3_256: "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376",
+                sha3_512: "04a371e84ecfb5b8b77cb48610fca8182dd457ce6f326a0fd3d7ec2f1e91636d\
+                           ee691fbe0c985302ba1b0d8dc78c086346b533b49c030d99a27daf1139d6e75e",
+                keccak256: "45d3b367a6904e6e8d502ee04999a7c27647f91fa845d456525fd352ae3d7371",
+            },
+        ];
+
+        for test in tests {
+            let hash = sha3_256::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, sha3_256::Hash::from_hex(test.sha3_256).expect("parse hex"));
+            assert_eq!(&hash.to_hex(), test.sha3_256);
+
+            let hash = sha3_512::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, sha3_512::Hash::from_hex(test.sha3_512).expect("parse hex"));
+            assert_eq!(&hash.to_hex(), test.sha3_512);
+
+            let hash = keccak256::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, keccak256::Hash::from_hex(test.keccak256).expect("parse hex"));
+            assert_eq!(&hash.to_hex(), test.keccak256);
+
+            // Hash through engine, checking that we can input byte by byte
+            let mut engine = sha3_256::Hash::engine();
+            for ch in test.input.as_bytes() {
+                engine.input(&[*ch]);
+            }
+            assert_eq!(engine.n_bytes_hashed(), test.input.len());
+            assert_eq!(&sha3_256::Hash::from_engine(engine).to_hex(), test.sha3_256);
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn shake() {
+        use crate::hex::ToHex;
+
+        let mut out = [0u8; 32];
+        Shake128::default().finalize_xof().squeeze(&mut out);
+        assert_eq!(out.to_hex(), "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26");
+
+        let mut engine = Shake256::default();
+        engine.input(b"abc");
+        let mut out = [0u8; 64];
+        engine.finalize_xof().squeeze(&mut out);
+        assert_eq!(
+            out.to_hex(),
+            "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739\
+             d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4",
+        );
+
+        // Output spanning several blocks must not depend on how it is read.
+        let mut engine = Shake128::default();
+        engine.input(&[0x63; 1000]);
+        let mut reader = engine.finalize_xof();
+        let mut first = [0u8; 100];
+        let mut rest = [0u8; 100];
+        reader.squeeze(&mut first);
+        for chunk in rest.chunks_mut(7) {
+            reader.squeeze(chunk);
+        }
+        assert_eq!(rest[68..].to_hex(), "2145488bb7e39baf9f2d759885068605fc3ed79ecb1915303a9a67e2797cba08");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sha3_256_serde() {
+        use serde_test::{Configure, Token, assert_tokens};
+
+        static HASH_BYTES: [u8; 32] = [
+            0x3a, 0x98, 0x5d, 0xa7, 0x4f, 0xe2, 0x25, 0xb2,
+            0x04, 0x5c, 0x17, 0x2d, 0x6b, 0xd3, 0x90, 0xbd,
+            0x85, 0x5f, 0x08, 0x6e, 0x3e, 0x9d, 0x52, 0x5b,
+            0x46, 0xbf, 0xe2, 0x45, 0x11, 0x43, 0x15, 0x32,
+        ];
+
+        let hash = sha3_256::Hash::from_slice(&HASH_BYTES).expect("right number of bytes");
+        assert_tokens(&hash.compact(), &[Token::BorrowedBytes(&HASH_BYTES[..])]);
+        assert_tokens(&hash.readable(), &[Token::Str("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532")]);
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use crate::{Hash, HashEngine};
+    use super::sha3_256;
+
+    #[bench]
+    pub fn sha3_256_10(bh: &mut Bencher) {
+        let mut engine = sha3_256::Hash::engine();
+        let bytes = [1u8; 10];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha3_256_1k(bh: &mut Bencher) {
+        let mut engine = sha3_256::Hash::engine();
+        let bytes = [1u8; 1024];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha3_256_64k(bh: &mut Bencher) {
+        let mut engine = sha3_256::Hash::engine();
+        let bytes = [1u8; 65536];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+}
@@ -29,7 +29,7 @@ use crate::{Error, HashEngine as _, hex};
 
 crate::internal_macros::hash_trait_impls!(512, false);
 
-const BLOCK_SIZE: usize = 128;
+pub(crate) const BLOCK_SIZE: usize = 128;
 
 /// Engine to compute SHA512 hash function.
 #[derive(Clone)]
@@ -80,6 +80,12 @@ impl crate::HashEngine for HashEngine {
     engine_input_impl!();
 }
 
+impl crate::FromMidState for HashEngine {
+    fn from_midstate(midstate: [u8; 64], length: usize) -> HashEngine {
+        HashEngine::from_midstate(midstate, length)
+    }
+}
+
 /// Output of the SHA256 hash function.
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[repr(transparent)]
@@ -89,7 +95,7 @@ pub struct Hash(
 );
 
 impl Hash {
-    fn internal_new(arr: [u8; 64]) -> Self {
+    const fn internal_new(arr: [u8; 64]) -> Self {
         Hash(arr)
     }
 
@@ -141,7 +147,7 @@ impl hash::Hash for Hash {
 }
 
 #[cfg(not(fuzzing))]
-fn from_engine(mut e: HashEngine) -> Hash {
+pub(crate) fn from_engine(mut e: HashEngine) -> Hash {
     // pad buffer with a single 1-bit then all 0s, until there are exactly 16 bytes remaining
     let data_len = e.length as u64;
 
@@ -162,7 +168,7 @@ fn from_engine(mut e: HashEngine) -> Hash {
 }
 
 #[cfg(fuzzing)]
-fn from_engine(e: HashEngine) -> Hash {
+pub(crate) fn from_engine(e: HashEngine) -> Hash {
     let mut hash = e.midstate();
     hash[0] ^= 0xff; // Make this distinct from SHA-256
     Hash(hash)
@@ -191,6 +197,62 @@ macro_rules! round(
 );
 
 impl HashEngine {
+    /// Constructs a hash engine suitable for use inside the default `sha384::HashEngine`.
+    pub(crate) fn sha384() -> Self {
+        HashEngine {
+            h: [
+                0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+                0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+            ],
+            length: 0,
+            buffer: [0; BLOCK_SIZE],
+        }
+    }
+
+    /// Constructs a hash engine suitable for use inside the default `sha512_256::HashEngine`.
+    pub(crate) fn sha512_256() -> Self {
+        HashEngine {
+            h: [
+                0x22312194fc2bf72c, 0x9f555fa3c84c64c2, 0x2393b86b6f53b151, 0x963877195940eabd,
+                0x96283ee2a88effe3, 0xbe5e1e2553863992, 0x2b0199fc2c85b8aa, 0x0eb72ddc81c52ca2,
+            ],
+            length: 0,
+            buffer: [0; BLOCK_SIZE],
+        }
+    }
+
+    /// Constructs a hash engine suitable for use inside the default `sha512_224::HashEngine`.
+    pub(crate) fn sha512_224() -> Self {
+        HashEngine {
+            h: [
+                0x8c3d37c819544da2, 0x73e1996689dcd4d6, 0x1dfab7ae32ff9c82, 0x679dd514582f9fcf,
+                0x0f6d2b697bd44da8, 0x77e36f7304c48942, 0x3f9d85a86a1d36c8, 0x1112e6ad91d692a1,
+            ],
+            length: 0,
+            buffer: [0; BLOCK_SIZE],
+        }
+    }
+
+    /// Create a new [`HashEngine`] from a midstate.
+    ///
+    /// # Panics
+    ///
+    /// If `length` is not a multiple of the block size.
+    pub fn from_midstate(midstate: [u8; 64], length: usize) -> HashEngine {
+        assert!(length % BLOCK_SIZE == 0, "length is no multiple of the block size");
+
+        let mut ret = [0; 8];
+        for (ret_val, midstate_bytes) in ret.iter_mut().zip(midstate.chunks_exact(8)) {
+            *ret_val = u64::from_be_bytes(midstate_bytes.try_into().expect("8 byte slice"));
+        }
+
+        HashEngine {
+            buffer: [0; BLOCK_SIZE],
+            h: ret,
+            length,
+        }
+    }
+
     // Algorithm copied from libsecp256k1
     fn process_block(&mut self) {
         debug_assert_eq!(self.buffer.len(), BLOCK_SIZE);
@@ -0,0 +1,217 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! SHA512_224 implementation.
+//!
+//! SHA512/224 is SHA512 with a different initial state and the output truncated to 28 bytes.
+//! It is a different hash function from SHA224, not just a truncated SHA512.
+//!
+
+use core::str;
+use core::ops::Index;
+use core::slice::SliceIndex;
+
+use crate::{Error, hex, sha512};
+
+crate::internal_macros::hash_type! {
+    224,
+    false,
+    "Output of the SHA512/224 hash function.",
+    "crate::util::json_hex_string::len_28"
+}
+
+fn from_engine(e: HashEngine) -> Hash {
+    let mut ret = [0; 28];
+    ret.copy_from_slice(&sha512::from_engine(e.0)[..28]);
+    Hash(ret)
+}
+
+/// Engine to compute SHA512/224 hash function.
+#[derive(Clone)]
+pub struct HashEngine(sha512::HashEngine);
+
+impl Default for HashEngine {
+    fn default() -> Self {
+        HashEngine(sha512::HashEngine::sha512_224())
+    }
+}
+
+impl HashEngine {
+    /// Create a new [`HashEngine`] from a midstate.
+    ///
+    /// # Panics
+    ///
+    /// If `length` is not a multiple of the block size.
+    pub fn from_midstate(midstate: [u8; 64], length: usize) -> HashEngine {
+        HashEngine(sha512::HashEngine::from_midstate(midstate, length))
+    }
+}
+
+impl crate::FromMidState for HashEngine {
+    fn from_midstate(midstate: [u8; 64], length: usize) -> HashEngine {
+        HashEngine::from_midstate(midstate, length)
+    }
+}
+
+impl crate::HashEngine for HashEngine {
+    type MidState = [u8; 64];
+
+    fn midstate(&self) -> [u8; 64] {
+        self.0.midstate()
+    }
+
+    const BLOCK_SIZE: usize = sha512::BLOCK_SIZE;
+
+    fn n_bytes_hashed(&self) -> usize {
+        self.0.n_bytes_hashed()
+    }
+
+    fn input(&mut self, inp: &[u8]) {
+        self.0.input(inp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn test() {
+        use crate::{sha512_224, Hash, HashEngine};
+        use crate::hex::{FromHex, ToHex};
+
+        #[derive(Clone)]
+        struct Test {
+            input: &'static str,
+            output: Vec<u8>,
+            output_str: &'static str,
+        }
+
+        let tests = vec![
+            // Test vectors from NIST and computed with Python's `hashlib`
+            Test {
+                input: "",
+                output: vec![
+                    0x6e, 0xd0, 0xdd, 0x02, 0x80, 0x6f, 0xa8, 0x9e,
+                    0x25, 0xde, 0x06, 0x0c, 0x19, 0xd3, 0xac, 0x86,
+                    0xca, 0xbb, 0x87, 0xd6, 0xa0, 0xdd, 0xd0, 0x5c,
+                    0x33, 0x3b, 0x84, 0xf4,
+                ],
+                output_str: "6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4",
+            },
+            Test {
+                input: "abc",
+                output: vec![
+                    0x46, 0x34, 0x27, 0x0f, 0x70, 0x7b, 0x6a, 0x54,
+                    0xda, 0xae, 0x75, 0x30, 0x46, 0x08, 0x42, 0xe2,
+                    0x0e, 0x37, 0xed, 0x26, 0x5c, 0xee, 0xe9, 0xa4,
+                    0x3e, 0x89, 0x24, 0xaa,
+                ],
+                output_str: "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa",
+            },
+            Test {
+                input: "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                output: vec![
+                    0x23, 0xfe, 0xc5, 0xbb, 0x94, 0xd6, 0x0b, 0x23,
+                    0x30, 0x81, 0x92, 0x64, 0x0b, 0x0c, 0x45, 0x33,
+                    0x35, 0xd6, 0x64, 0x73, 0x4f, 0xe4, 0x0e, 0x72,
+                    0x68, 0x67, 0x4a, 0xf9,
+                ],
+                output_str: "23fec5bb94d60b23308192640b0c453335d664734fe40e7268674af9",
+            },
+            Test {
+                input: "The quick brown fox jumps over the lazy dog",
+                output: vec![
+                    0x94, 0x4c, 0xd2, 0x84, 0x7f, 0xb5, 0x45, 0x58,
+                    0xd4, 0x77, 0x5d, 0xb0, 0x48, 0x5a, 0x50, 0x00,
+                    0x31, 0x11, 0xc8, 0xe5, 0xda, 0xa6, 0x3f, 0xe7,
+                    0x22, 0xc6, 0xaa, 0x37,
+                ],
+                output_str: "944cd2847fb54558d4775db0485a50003111c8e5daa63fe722c6aa37",
+            },
+        ];
+
+        for test in tests {
+            // Hash through high-level API, check hex encoding/decoding
+            let hash = sha512_224::Hash::hash(test.input.as_bytes());
+            assert_eq!(hash, sha512_224::Hash::from_hex(test.output_str).expect("parse hex"));
+            assert_eq!(&hash[..], &test.output[..]);
+            assert_eq!(&hash.to_hex(), &test.output_str);
+
+            // Hash through engine, checking that we can input byte by byte
+            let mut engine = sha512_224::Hash::engine();
+            for ch in test.input.as_bytes() {
+                engine.input(&[*ch]);
+            }
+            let manual_hash = sha512_224::Hash::from_engine(engine);
+            assert_eq!(hash, manual_hash);
+            assert_eq!(hash.into_inner()[..].as_ref(), test.output.as_slice());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sha512_224_serde() {
+        use serde_test::{Configure, Token, assert_tokens};
+        use crate::{sha512_224, Hash};
+
+        static HASH_BYTES: [u8; 28] = [
+            0x46, 0x34, 0x27, 0x0f, 0x70, 0x7b, 0x6a, 0x54,
+            0xda, 0xae, 0x75, 0x30, 0x46, 0x08, 0x42, 0xe2,
+            0x0e, 0x37, 0xed, 0x26, 0x5c, 0xee, 0xe9, 0xa4,
+            0x3e, 0x89, 0x24, 0xaa,
+        ];
+
+        let hash = sha512_224::Hash::from_slice(&HASH_BYTES).expect("right number of bytes");
+        assert_tokens(&hash.compact(), &[Token::BorrowedBytes(&HASH_BYTES[..])]);
+        assert_tokens(&hash.readable(), &[Token::Str("4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa")]);
+    }
+}
+
+#[cfg(bench)]
+mod benches {
+    use test::Bencher;
+
+    use crate::{Hash, HashEngine, sha512_224};
+
+    #[bench]
+    pub fn sha512_224_10(bh: &mut Bencher) {
+        let mut engine = sha512_224::Hash::engine();
+        let bytes = [1u8; 10];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha512_224_1k(bh: &mut Bencher) {
+        let mut engine = sha512_224::Hash::engine();
+        let bytes = [1u8; 1024];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+    #[bench]
+    pub fn sha512_224_64k(bh: &mut Bencher) {
+        let mut engine = sha512_224::Hash::engine();
+        let bytes = [1u8; 65536];
+        bh.iter( || {
+            engine.input(&bytes);
+        });
+        bh.bytes = bytes.len() as u64;
+    }
+
+}
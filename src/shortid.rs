@@ -0,0 +1,176 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! Short transaction IDs.
+//!
+//! Compact block relay (BIP152) and transaction reconciliation (BIP330, "Erlay") both identify
+//! transactions by running SipHash-2-4 over their (w)txid, keyed by per-block or per-connection
+//! secrets. This module derives those keys and computes the short IDs.
+//!
+
+use crate::{sha256, siphash24, Hash, HashEngine};
+use crate::sha256t::tags::TxRelaySaltingHash;
+
+/// Splits the first 16 bytes of a hash into SipHash keys.
+fn keys_from_hash(hash: &[u8]) -> siphash24::HashEngine {
+    let mut k0 = [0; 8];
+    let mut k1 = [0; 8];
+    k0.copy_from_slice(&hash[0..8]);
+    k1.copy_from_slice(&hash[8..16]);
+    siphash24::HashEngine::with_keys(u64::from_le_bytes(k0), u64::from_le_bytes(k1))
+}
+
+/// SipHash keys of the BIP152 short IDs of a compact block.
+#[derive(Debug, Clone)]
+pub struct CompactBlockKeys {
+    engine: siphash24::HashEngine,
+}
+
+impl CompactBlockKeys {
+    /// Derives the keys from the serialized block header and the nonce of a `cmpctblock` message.
+    pub fn new(header: &[u8; 80], nonce: u64) -> CompactBlockKeys {
+        let mut engine = sha256::Hash::engine();
+        engine.input(header);
+        engine.input(&nonce.to_le_bytes());
+        CompactBlockKeys { engine: keys_from_hash(&sha256::Hash::from_engine(engine)[..]) }
+    }
+
+    /// Creates the keys from their raw values.
+    pub fn from_keys(k0: u64, k1: u64) -> CompactBlockKeys {
+        CompactBlockKeys { engine: siphash24::HashEngine::with_keys(k0, k1) }
+    }
+
+    /// Returns the SipHash keys.
+    pub fn keys(&self) -> (u64, u64) {
+        self.engine.keys()
+    }
+
+    /// Computes the short ID of the transaction with (w)txid `txid`, in internal byte order.
+    ///
+    /// The ID is returned as serialized in a `cmpctblock` message.
+    pub fn short_id(&self, txid: &[u8]) -> [u8; 6] {
+        let mut engine = self.engine.clone();
+        engine.input(txid);
+        let id = siphash24::Hash::from_engine_to_u64(engine).to_le_bytes();
+        let mut ret = [0; 6];
+        ret.copy_from_slice(&id[..6]);
+        ret
+    }
+
+    /// Computes the short IDs of a batch of transactions.
+    pub fn short_ids<'a, I>(&'a self, txids: I) -> impl Iterator<Item = [u8; 6]> + 'a
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+        I::IntoIter: 'a,
+    {
+        txids.into_iter().map(move |txid| self.short_id(txid.as_ref()))
+    }
+}
+
+/// SipHash keys of the BIP330 short IDs of a reconciliation.
+#[derive(Debug, Clone)]
+pub struct ReconciliationKeys {
+    engine: siphash24::HashEngine,
+}
+
+impl ReconciliationKeys {
+    /// Derives the keys from the salts exchanged by both peers in `sendtxrcncl` messages.
+    ///
+    /// The order of the salts does not matter.
+    pub fn new(salt1: u64, salt2: u64) -> ReconciliationKeys {
+        let mut engine = TxRelaySaltingHash::engine();
+        engine.input(&salt1.min(salt2).to_le_bytes());
+        engine.input(&salt1.max(salt2).to_le_bytes());
+        ReconciliationKeys { engine: keys_from_hash(&TxRelaySaltingHash::from_engine(engine)[..]) }
+    }
+
+    /// Creates the keys from their raw values.
+    pub fn from_keys(k0: u64, k1: u64) -> ReconciliationKeys {
+        ReconciliationKeys { engine: siphash24::HashEngine::with_keys(k0, k1) }
+    }
+
+    /// Returns the SipHash keys.
+    pub fn keys(&self) -> (u64, u64) {
+        self.engine.keys()
+    }
+
+    /// Computes the short ID of the transaction with wtxid `wtxid`, in internal byte order.
+    ///
+    /// Short IDs are never zero, as required by the sketches they are added to.
+    pub fn short_id(&self, wtxid: &[u8]) -> u32 {
+        let mut engine = self.engine.clone();
+        engine.input(wtxid);
+        1 + (siphash24::Hash::from_engine_to_u64(engine) % 0xffffffff) as u32
+    }
+
+    /// Computes the short IDs of a batch of transactions.
+    pub fn short_ids<'a, I>(&'a self, wtxids: I) -> impl Iterator<Item = u32> + 'a
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+        I::IntoIter: 'a,
+    {
+        wtxids.into_iter().map(move |wtxid| self.short_id(wtxid.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    use crate::hex::FromHex;
+    use crate::sha256d;
+
+    // Mainnet genesis block
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    const HEADER: &str = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+    const TXID: &str = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+
+    fn txids() -> [[u8; 32]; 2] {
+        [
+            TXID.parse::<sha256d::Hash>().unwrap().into_inner(),
+            sha256::Hash::hash(b"second").into_inner(),
+        ]
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "alloc"))]
+    fn compact_block() {
+        let mut header = [0; 80];
+        header.copy_from_slice(&Vec::from_hex(HEADER).unwrap());
+        let keys = CompactBlockKeys::new(&header, 0x0123456789abcdef);
+        assert_eq!(keys.keys(), (0x52f1458bc3816130, 0xd82182c249af75c6));
+
+        let txids = txids();
+        assert_eq!(keys.short_id(&txids[0]), [0xdd, 0xbd, 0x83, 0x31, 0x7c, 0xd9]);
+        let ids: Vec<[u8; 6]> = keys.short_ids(&txids).collect();
+        assert_eq!(ids, [[0xdd, 0xbd, 0x83, 0x31, 0x7c, 0xd9], [0x1e, 0x37, 0xc5, 0xc4, 0x4e, 0x21]]);
+
+        let from_keys = CompactBlockKeys::from_keys(0x52f1458bc3816130, 0xd82182c249af75c6);
+        assert_eq!(from_keys.short_id(&txids[1]), ids[1]);
+    }
+
+    #[test]
+    fn reconciliation() {
+        let keys = ReconciliationKeys::new(0xdeadbeef, 42);
+        assert_eq!(keys.keys(), (0xde02a07b6b1d475d, 0x7d185c5690b54cf5));
+        assert_eq!(ReconciliationKeys::new(42, 0xdeadbeef).keys(), keys.keys());
+
+        let txids = txids();
+        assert_eq!(keys.short_id(&txids[0]), 1282750289);
+        let ids: Vec<u32> = keys.short_ids(txids.iter()).collect();
+        assert_eq!(ids, [1282750289, 438525690]);
+    }
+}
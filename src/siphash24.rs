@@ -19,8 +19,15 @@
 
 //! SipHash 2-4 implementation.
 //!
+//! The engine is generic over the [`Variant`] of SipHash, which is also used by the [`siphash13`]
+//! and [`siphash24_128`] modules.
+//!
+//! [`siphash13`]: crate::siphash13
+//! [`siphash24_128`]: crate::siphash24_128
+//!
 
-use core::{cmp, mem, ptr, str};
+use core::{cmp, fmt, mem, ptr, str};
+use core::marker::PhantomData;
 use core::ops::Index;
 use core::slice::SliceIndex;
 
@@ -44,7 +51,6 @@ fn from_engine(e: HashEngine) -> Hash {
     Hash::from_u64(state.v0 ^ state.v1 ^ state.v2 ^ state.v3)
 }
 
-
 macro_rules! compress {
     ($state:expr) => {{
         compress!($state.v0, $state.v1, $state.v2, $state.v3)
@@ -85,6 +91,46 @@ macro_rules! load_int_le {
     }};
 }
 
+/// Round counts and output width of a SipHash variant.
+pub trait Variant: Clone + fmt::Debug {
+    /// Number of compression rounds per message block.
+    const C_ROUNDS: usize;
+    /// Number of finalization rounds.
+    const D_ROUNDS: usize;
+    /// Whether the variant has a 128-bit output rather than a 64-bit one.
+    const WIDE: bool;
+}
+
+/// SipHash-2-4 with a 64-bit output, the original SipHash.
+#[derive(Debug, Clone)]
+pub struct Sip24;
+
+impl Variant for Sip24 {
+    const C_ROUNDS: usize = 2;
+    const D_ROUNDS: usize = 4;
+    const WIDE: bool = false;
+}
+
+/// SipHash-1-3 with a 64-bit output, as used by the Rust standard library.
+#[derive(Debug, Clone)]
+pub struct Sip13;
+
+impl Variant for Sip13 {
+    const C_ROUNDS: usize = 1;
+    const D_ROUNDS: usize = 3;
+    const WIDE: bool = false;
+}
+
+/// SipHash-2-4 with a 128-bit output.
+#[derive(Debug, Clone)]
+pub struct Sip24Wide;
+
+impl Variant for Sip24Wide {
+    const C_ROUNDS: usize = 2;
+    const D_ROUNDS: usize = 4;
+    const WIDE: bool = true;
+}
+
 /// Internal state of the [`HashEngine`].
 #[derive(Debug, Clone)]
 pub struct State {
@@ -98,38 +144,43 @@ pub struct State {
     v3: u64,
 }
 
-/// Engine to compute the SipHash24 hash function.
+/// Engine to compute a SipHash variant.
 #[derive(Debug, Clone)]
-pub struct HashEngine {
+pub struct SipHashEngine<V: Variant> {
     k0: u64,
     k1: u64,
     length: usize, // how many bytes we've processed
     state: State,  // hash State
     tail: u64,     // unprocessed bytes le
     ntail: usize,  // how many bytes in tail are valid
+    variant: PhantomData<V>,
 }
 
-impl HashEngine {
-    /// Creates a new SipHash24 engine with keys.
-    pub fn with_keys(k0: u64, k1: u64) -> HashEngine {
-        HashEngine {
+/// Engine to compute the SipHash24 hash function.
+pub type HashEngine = SipHashEngine<Sip24>;
+
+impl<V: Variant> SipHashEngine<V> {
+    /// Creates a new SipHash engine with keys.
+    pub fn with_keys(k0: u64, k1: u64) -> SipHashEngine<V> {
+        SipHashEngine {
             k0,
             k1,
             length: 0,
             state: State {
                 v0: k0 ^ 0x736f6d6570736575,
-                v1: k1 ^ 0x646f72616e646f6d,
+                v1: k1 ^ 0x646f72616e646f6d ^ if V::WIDE { 0xee } else { 0 },
                 v2: k0 ^ 0x6c7967656e657261,
                 v3: k1 ^ 0x7465646279746573,
             },
             tail: 0,
             ntail: 0,
+            variant: PhantomData,
         }
     }
 
-    /// Creates a new SipHash24 engine.
-    pub fn new() -> HashEngine {
-        HashEngine::with_keys(0, 0)
+    /// Creates a new SipHash engine.
+    pub fn new() -> SipHashEngine<V> {
+        SipHashEngine::with_keys(0, 0)
     }
 
     /// Retrieves the keys of this engine.
@@ -139,26 +190,66 @@ impl HashEngine {
 
     #[inline]
     fn c_rounds(state: &mut State) {
-        compress!(state);
-        compress!(state);
+        for _ in 0..V::C_ROUNDS {
+            compress!(state);
+        }
     }
 
     #[inline]
     fn d_rounds(state: &mut State) {
-        compress!(state);
-        compress!(state);
-        compress!(state);
-        compress!(state);
+        for _ in 0..V::D_ROUNDS {
+            compress!(state);
+        }
+    }
+
+    /// Processes the last block and runs the finalization rounds producing the first 64 bits
+    /// of output.
+    #[inline]
+    fn finalize_state(&self) -> State {
+        let mut state = self.state.clone();
+
+        let b: u64 = ((self.length as u64 & 0xff) << 56) | self.tail;
+
+        state.v3 ^= b;
+        Self::c_rounds(&mut state);
+        state.v0 ^= b;
+
+        state.v2 ^= if V::WIDE { 0xee } else { 0xff };
+        Self::d_rounds(&mut state);
+
+        state
+    }
+
+    /// Produces the 64-bit output of a narrow variant.
+    #[inline]
+    pub(crate) fn finalize_to_u64(&self) -> u64 {
+        debug_assert!(!V::WIDE);
+        let state = self.finalize_state();
+        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+    }
+
+    /// Produces the 128-bit output of a wide variant.
+    #[inline]
+    pub(crate) fn finalize_to_u128(&self) -> u128 {
+        debug_assert!(V::WIDE);
+        let mut state = self.finalize_state();
+        let low = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
+
+        state.v1 ^= 0xdd;
+        Self::d_rounds(&mut state);
+        let high = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
+
+        (u128::from(high) << 64) | u128::from(low)
     }
 }
 
-impl Default for HashEngine {
+impl<V: Variant> Default for SipHashEngine<V> {
     fn default() -> Self {
-        HashEngine::new()
+        SipHashEngine::new()
     }
 }
 
-impl crate::HashEngine for HashEngine {
+impl<V: Variant> crate::HashEngine for SipHashEngine<V> {
     type MidState = State;
 
     fn midstate(&self) -> State {
@@ -182,7 +273,7 @@ impl crate::HashEngine for HashEngine {
                 return;
             } else {
                 self.state.v3 ^= self.tail;
-                HashEngine::c_rounds(&mut self.state);
+                Self::c_rounds(&mut self.state);
                 self.state.v0 ^= self.tail;
                 self.ntail = 0;
             }
@@ -197,7 +288,7 @@ impl crate::HashEngine for HashEngine {
             let mi = unsafe { load_int_le!(msg, i, u64) };
 
             self.state.v3 ^= mi;
-            HashEngine::c_rounds(&mut self.state);
+            Self::c_rounds(&mut self.state);
             self.state.v0 ^= mi;
 
             i += 8;
@@ -231,18 +322,7 @@ impl Hash {
     /// Produces a hash as `u64` from the current state of a given engine.
     #[inline]
     pub fn from_engine_to_u64(e: HashEngine) -> u64 {
-        let mut state = e.state;
-
-        let b: u64 = ((e.length as u64 & 0xff) << 56) | e.tail;
-
-        state.v3 ^= b;
-        HashEngine::c_rounds(&mut state);
-        state.v0 ^= b;
-
-        state.v2 ^= 0xff;
-        HashEngine::d_rounds(&mut state);
-
-        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+        e.finalize_to_u64()
     }
 
     /// Returns the (little endian) 64-bit integer representation of the hash value.
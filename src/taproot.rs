@@ -0,0 +1,485 @@
+// Bitcoin Hashes Library
+// Written in 2022 by
+//   The rust-bitcoin developers.
+//
+// To the extent possible under law, the author(s) have dedicated all
+// copyright and related and neighboring rights to this software to
+// the public domain worldwide. This software is distributed without
+// any warranty.
+//
+// You should have received a copy of the CC0 Public Domain Dedication
+// along with this software.
+// If not, see <http://creativecommons.org/publicdomain/zero/1.0/>.
+//
+
+//! BIP341 taproot script trees.
+//!
+//! Leaves are hashed with the `TapLeaf` tag and branches with the `TapBranch` tag, the two
+//! children of a branch being sorted lexicographically. A tree is built either from its leaves
+//! and their depths, in depth-first order, or from weighted leaves, the likeliest spending paths
+//! getting the shortest merkle branches. Tweaking the internal key with the root needs elliptic
+//! curve operations and is left to the caller.
+//!
+
+use core::cmp::Reverse;
+use core::fmt;
+
+use crate::alloc::collections::BinaryHeap;
+use crate::alloc::vec::Vec;
+use crate::alloc::vec;
+use crate::{Hash, HashEngine};
+
+pub use crate::sha256t::tags::{TapBranchHash, TapBranchTag, TapLeafHash, TapLeafTag};
+
+/// The leaf version of BIP342 tapscript.
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// The maximum depth of a leaf, i.e. the maximum number of hashes in a merkle branch.
+pub const MAX_DEPTH: usize = 128;
+
+/// Size of a control block without any merkle branch.
+const CONTROL_BLOCK_BASE_SIZE: usize = 33;
+
+impl TapLeafHash {
+    /// Computes the hash of the leaf with version `leaf_version` and script `script`.
+    pub fn from_script(script: &[u8], leaf_version: u8) -> TapLeafHash {
+        let mut engine = TapLeafHash::engine();
+        engine.input(&[leaf_version]);
+        let len = script.len() as u64;
+        match len {
+            0..=0xfc => engine.input(&[len as u8]),
+            0xfd..=0xffff => {
+                engine.input(&[0xfd]);
+                engine.input(&(len as u16).to_le_bytes());
+            }
+            0x10000..=0xffffffff => {
+                engine.input(&[0xfe]);
+                engine.input(&(len as u32).to_le_bytes());
+            }
+            _ => {
+                engine.input(&[0xff]);
+                engine.input(&len.to_le_bytes());
+            }
+        }
+        engine.input(script);
+        TapLeafHash::from_engine(engine)
+    }
+}
+
+impl From<TapLeafHash> for TapBranchHash {
+    fn from(leaf: TapLeafHash) -> TapBranchHash {
+        TapBranchHash::from_inner(leaf.into_inner())
+    }
+}
+
+impl TapBranchHash {
+    /// Computes the hash of the branch with children `a` and `b`, in either order.
+    pub fn from_node_hashes(a: TapBranchHash, b: TapBranchHash) -> TapBranchHash {
+        let (min, max) = if a < b { (a, b) } else { (b, a) };
+        let mut engine = TapBranchHash::engine();
+        engine.input(&min[..]);
+        engine.input(&max[..]);
+        TapBranchHash::from_engine(engine)
+    }
+}
+
+/// Checks that `leaf_version` can be used in a script tree: it must be even, and not 0x50,
+/// which would be mistaken for an annex.
+pub fn check_leaf_version(leaf_version: u8) -> Result<(), Error> {
+    if leaf_version & 1 != 0 || leaf_version == 0x50 {
+        return Err(Error::InvalidLeafVersion(leaf_version));
+    }
+    Ok(())
+}
+
+/// Computes the root of the tree containing the leaf with hash `leaf_hash`, given the merkle
+/// branch of the leaf from the bottom up.
+pub fn compute_root(leaf_hash: TapLeafHash, merkle_branch: &[TapBranchHash]) -> TapBranchHash {
+    merkle_branch.iter().fold(leaf_hash.into(), |node, sibling| TapBranchHash::from_node_hashes(node, *sibling))
+}
+
+/// Checks that the leaf with hash `leaf_hash` and merkle branch `merkle_branch` is in the tree
+/// with root `root`.
+pub fn verify_merkle_branch(root: &TapBranchHash, leaf_hash: TapLeafHash, merkle_branch: &[TapBranchHash]) -> bool {
+    merkle_branch.len() <= MAX_DEPTH && compute_root(leaf_hash, merkle_branch) == *root
+}
+
+/// Checks that `script` is committed to by `control_block` in the tree with root `root`.
+///
+/// The internal key and the parity of the output key are not checked, as that needs the output
+/// key and elliptic curve operations.
+pub fn verify_control_block(root: &TapBranchHash, script: &[u8], control_block: &[u8]) -> Result<bool, Error> {
+    let len = control_block.len();
+    if len < CONTROL_BLOCK_BASE_SIZE
+        || (len - CONTROL_BLOCK_BASE_SIZE) % 32 != 0
+        || (len - CONTROL_BLOCK_BASE_SIZE) / 32 > MAX_DEPTH
+    {
+        return Err(Error::InvalidControlBlockSize(len));
+    }
+    let leaf_version = control_block[0] & 0xfe;
+    check_leaf_version(leaf_version)?;
+
+    let leaf_hash = TapLeafHash::from_script(script, leaf_version);
+    let root_from_branch = control_block[CONTROL_BLOCK_BASE_SIZE..]
+        .chunks(32)
+        .map(|sibling| TapBranchHash::from_slice(sibling).expect("chunks are 32 bytes"))
+        .fold(leaf_hash.into(), TapBranchHash::from_node_hashes);
+    Ok(root_from_branch == *root)
+}
+
+/// Error of building a script tree or parsing a control block.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The tree has no leaves.
+    EmptyTree,
+    /// The leaves do not form a complete binary tree.
+    IncompleteTree,
+    /// A leaf is deeper than [`MAX_DEPTH`].
+    MaxDepthExceeded,
+    /// The leaf version is odd or 0x50.
+    InvalidLeafVersion(u8),
+    /// The control block has an invalid size.
+    InvalidControlBlockSize(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::EmptyTree => f.write_str("script tree has no leaves"),
+            Error::IncompleteTree => f.write_str("leaves do not form a complete script tree"),
+            Error::MaxDepthExceeded => write!(f, "script tree is deeper than {}", MAX_DEPTH),
+            Error::InvalidLeafVersion(v) => write!(f, "invalid leaf version {:#04x}", v),
+            Error::InvalidControlBlockSize(len) => write!(f, "invalid control block size {}", len),
+        }
+    }
+}
+
+/// A leaf of a script tree.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TapLeaf {
+    leaf_version: u8,
+    script: Vec<u8>,
+    /// Siblings of the path from the leaf to the root, from the bottom up.
+    merkle_branch: Vec<TapBranchHash>,
+}
+
+impl TapLeaf {
+    /// Returns the leaf version.
+    pub fn leaf_version(&self) -> u8 {
+        self.leaf_version
+    }
+
+    /// Returns the script.
+    pub fn script(&self) -> &[u8] {
+        &self.script
+    }
+
+    /// Returns the hash of the leaf.
+    pub fn leaf_hash(&self) -> TapLeafHash {
+        TapLeafHash::from_script(&self.script, self.leaf_version)
+    }
+
+    /// Returns the depth of the leaf, zero if it is the root.
+    pub fn depth(&self) -> usize {
+        self.merkle_branch.len()
+    }
+
+    /// Returns the merkle branch of the leaf, from the bottom up.
+    pub fn merkle_branch(&self) -> &[TapBranchHash] {
+        &self.merkle_branch
+    }
+
+    /// Serializes the control block spending the output through this leaf, given the x-only
+    /// internal key and the parity of the y coordinate of the output key.
+    pub fn control_block(&self, internal_key: &[u8; 32], output_key_parity: bool) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(CONTROL_BLOCK_BASE_SIZE + 32 * self.merkle_branch.len());
+        ret.push(self.leaf_version | output_key_parity as u8);
+        ret.extend_from_slice(internal_key);
+        for hash in &self.merkle_branch {
+            ret.extend_from_slice(&hash[..]);
+        }
+        ret
+    }
+
+    /// Checks that the leaf is in the tree with root `root`.
+    pub fn verify(&self, root: &TapBranchHash) -> bool {
+        verify_merkle_branch(root, self.leaf_hash(), &self.merkle_branch)
+    }
+}
+
+/// A node of a tree being built, with the indices of the leaves under it.
+struct Node {
+    hash: TapBranchHash,
+    leaves: Vec<usize>,
+}
+
+/// A complete script tree.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TapTree {
+    root: TapBranchHash,
+    leaves: Vec<TapLeaf>,
+}
+
+impl TapTree {
+    /// Builds a tree from leaves given as `(weight, leaf_version, script)`, so that a leaf
+    /// which is more likely to be spent gets a shorter merkle branch.
+    pub fn with_huffman_tree<I, S>(leaves: I) -> Result<TapTree, Error>
+    where
+        I: IntoIterator<Item = (u32, u8, S)>,
+        S: Into<Vec<u8>>,
+    {
+        let mut tree_leaves = Vec::new();
+        // The nodes not merged yet, indexed by their position in the heap so that ties between
+        // weights are broken deterministically.
+        let mut nodes = Vec::new();
+        let mut heap = BinaryHeap::new();
+        for (weight, leaf_version, script) in leaves {
+            check_leaf_version(leaf_version)?;
+            let leaf = TapLeaf { leaf_version, script: script.into(), merkle_branch: Vec::new() };
+            heap.push(Reverse((u64::from(weight), nodes.len())));
+            nodes.push(Some(Node { hash: leaf.leaf_hash().into(), leaves: vec![tree_leaves.len()] }));
+            tree_leaves.push(leaf);
+        }
+        if tree_leaves.is_empty() {
+            return Err(Error::EmptyTree);
+        }
+
+        while let Some(Reverse((weight_a, a))) = heap.pop() {
+            let a = nodes[a].take().expect("each node is popped once");
+            let Reverse((weight_b, b)) = match heap.pop() {
+                Some(b) => b,
+                None => {
+                    if tree_leaves.iter().any(|leaf| leaf.depth() > MAX_DEPTH) {
+                        return Err(Error::MaxDepthExceeded);
+                    }
+                    return Ok(TapTree { root: a.hash, leaves: tree_leaves });
+                }
+            };
+            let b = nodes[b].take().expect("each node is popped once");
+            heap.push(Reverse((weight_a + weight_b, nodes.len())));
+            nodes.push(Some(combine(&mut tree_leaves, a, b)));
+        }
+        unreachable!("the heap is not empty")
+    }
+
+    /// Returns the merkle root of the tree.
+    pub fn root(&self) -> TapBranchHash {
+        self.root
+    }
+
+    /// Returns the leaves of the tree, in depth-first order for a tree built with a
+    /// [`TapTreeBuilder`] and in the order they were given otherwise.
+    pub fn leaves(&self) -> &[TapLeaf] {
+        &self.leaves
+    }
+}
+
+/// Merges two nodes into their parent, extending the merkle branches of their leaves.
+fn combine(tree_leaves: &mut [TapLeaf], mut a: Node, b: Node) -> Node {
+    for leaf in &a.leaves {
+        tree_leaves[*leaf].merkle_branch.push(b.hash);
+    }
+    for leaf in &b.leaves {
+        tree_leaves[*leaf].merkle_branch.push(a.hash);
+    }
+    a.leaves.extend_from_slice(&b.leaves);
+    Node { hash: TapBranchHash::from_node_hashes(a.hash, b.hash), leaves: a.leaves }
+}
+
+/// Builds a script tree from its leaves and their depths, in depth-first order.
+pub struct TapTreeBuilder {
+    leaves: Vec<TapLeaf>,
+    /// The roots of the complete subtrees built so far, with their depths.
+    stack: Vec<(usize, Node)>,
+}
+
+impl Default for TapTreeBuilder {
+    fn default() -> Self {
+        TapTreeBuilder::new()
+    }
+}
+
+impl TapTreeBuilder {
+    /// Creates a builder for an empty tree.
+    pub fn new() -> TapTreeBuilder {
+        TapTreeBuilder { leaves: Vec::new(), stack: Vec::new() }
+    }
+
+    /// Adds the next leaf, in depth-first order, at depth `depth`.
+    ///
+    /// The leaf is merged with its sibling as soon as the subtree of the sibling is complete.
+    pub fn add_leaf(&mut self, depth: usize, leaf_version: u8, script: &[u8]) -> Result<(), Error> {
+        if depth > MAX_DEPTH {
+            return Err(Error::MaxDepthExceeded);
+        }
+        check_leaf_version(leaf_version)?;
+
+        let leaf = TapLeaf { leaf_version, script: script.to_vec(), merkle_branch: Vec::new() };
+        let mut node = Node { hash: leaf.leaf_hash().into(), leaves: vec![self.leaves.len()] };
+        self.leaves.push(leaf);
+
+        let mut depth = depth;
+        while self.stack.last().map_or(false, |(top, _)| *top == depth) {
+            if depth == 0 {
+                return Err(Error::IncompleteTree);
+            }
+            let (_, sibling) = self.stack.pop().expect("stack is not empty");
+            node = combine(&mut self.leaves, sibling, node);
+            depth -= 1;
+        }
+        self.stack.push((depth, node));
+        Ok(())
+    }
+
+    /// Returns the tree, if the leaves added form a complete tree.
+    pub fn finalize(mut self) -> Result<TapTree, Error> {
+        match self.stack.pop() {
+            None => Err(Error::EmptyTree),
+            Some((0, root)) if self.stack.is_empty() => Ok(TapTree { root: root.hash, leaves: self.leaves }),
+            Some(_) => Err(Error::IncompleteTree),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::{FromHex, ToHex};
+
+    #[test]
+    fn single_leaf() {
+        // From the BIP341 wallet test vectors
+        let script = Vec::from_hex("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac").unwrap();
+        let leaf_hash = "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21";
+        assert_eq!(TapLeafHash::from_script(&script, TAPSCRIPT_LEAF_VERSION).to_string(), leaf_hash);
+
+        let mut builder = TapTreeBuilder::new();
+        builder.add_leaf(0, TAPSCRIPT_LEAF_VERSION, &script).unwrap();
+        let tree = builder.finalize().unwrap();
+        assert_eq!(tree.root().to_string(), leaf_hash);
+        assert_eq!(tree.leaves()[0].depth(), 0);
+        assert!(tree.leaves()[0].verify(&tree.root()));
+
+        let tree = TapTree::with_huffman_tree(Some((1, TAPSCRIPT_LEAF_VERSION, script))).unwrap();
+        assert_eq!(tree.root().to_string(), leaf_hash);
+    }
+
+    #[test]
+    fn three_leaves() {
+        // Checked against a Python implementation of BIP341
+        let mut builder = TapTreeBuilder::new();
+        builder.add_leaf(1, TAPSCRIPT_LEAF_VERSION, &[0x51]).unwrap();
+        builder.add_leaf(2, TAPSCRIPT_LEAF_VERSION, &[0x52]).unwrap();
+        builder.add_leaf(2, 0xfa, &[0x53, 0x87]).unwrap();
+        let tree = builder.finalize().unwrap();
+
+        let a = "a85b2107f791b26a84e7586c28cec7cb61202ed3d01944d832500f363782d675";
+        let b = "c276fef1386890619b80e10a4a328572d97493add269df1a15a7f89f8ae8ec09";
+        let c = "dbfa10bc53971a19c77aaaea2f7b7cacca149c3ae199360c585daea81252c763";
+        let bc = "53c3fd85bcda96331e4b240282e3acfd94fc0b07eef2f62dd3680d6ab09d4182";
+        let root = "2e3e95f626bc18ed5f9bc690409b8cdeb3fedfc6660a02e8d8f2cbb388b442d4";
+        assert_eq!(tree.root().to_string(), root);
+
+        let leaves = tree.leaves();
+        assert_eq!(leaves.len(), 3);
+        assert_eq!(leaves[0].leaf_hash().to_string(), a);
+        assert_eq!(leaves[1].leaf_hash().to_string(), b);
+        assert_eq!(leaves[2].leaf_hash().to_string(), c);
+        assert_eq!(leaves[0].merkle_branch(), &[bc.parse().unwrap()]);
+        assert_eq!(leaves[1].merkle_branch(), &[c.parse().unwrap(), a.parse().unwrap()]);
+        assert_eq!(leaves[2].merkle_branch(), &[b.parse().unwrap(), a.parse().unwrap()]);
+        for leaf in leaves {
+            assert!(leaf.verify(&tree.root()));
+            assert!(!leaf.verify(&leaf.leaf_hash().into()));
+        }
+
+        let mut internal_key = [0; 32];
+        for (i, byte) in internal_key.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let control_block = leaves[1].control_block(&internal_key, true);
+        assert_eq!(
+            control_block.to_hex(),
+            "c1000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f\
+             dbfa10bc53971a19c77aaaea2f7b7cacca149c3ae199360c585daea81252c763\
+             a85b2107f791b26a84e7586c28cec7cb61202ed3d01944d832500f363782d675",
+        );
+        assert_eq!(verify_control_block(&tree.root(), &[0x52], &control_block), Ok(true));
+        assert_eq!(verify_control_block(&tree.root(), &[0x53, 0x87], &control_block), Ok(false));
+        let control_block = leaves[2].control_block(&internal_key, false);
+        assert_eq!(control_block[0], 0xfa);
+        assert_eq!(verify_control_block(&tree.root(), &[0x53, 0x87], &control_block), Ok(true));
+        assert_eq!(
+            verify_control_block(&tree.root(), &[0x53, 0x87], &control_block[..64]),
+            Err(Error::InvalidControlBlockSize(64)),
+        );
+    }
+
+    #[test]
+    fn huffman() {
+        let scripts: [&[u8]; 4] = [&[0x51], &[0x52], &[0x53], &[0x54]];
+        let tree = TapTree::with_huffman_tree(vec![
+            (1, TAPSCRIPT_LEAF_VERSION, scripts[0]),
+            (8, TAPSCRIPT_LEAF_VERSION, scripts[1]),
+            (2, TAPSCRIPT_LEAF_VERSION, scripts[2]),
+            (4, TAPSCRIPT_LEAF_VERSION, scripts[3]),
+        ]).unwrap();
+        let depths: Vec<usize> = tree.leaves().iter().map(TapLeaf::depth).collect();
+        assert_eq!(depths, [3, 1, 3, 2]);
+        for leaf in tree.leaves() {
+            assert!(leaf.verify(&tree.root()));
+        }
+
+        // Siblings are sorted, so any tree of the same shape has the same root
+        let mut builder = TapTreeBuilder::new();
+        builder.add_leaf(1, TAPSCRIPT_LEAF_VERSION, scripts[1]).unwrap();
+        builder.add_leaf(2, TAPSCRIPT_LEAF_VERSION, scripts[3]).unwrap();
+        builder.add_leaf(3, TAPSCRIPT_LEAF_VERSION, scripts[0]).unwrap();
+        builder.add_leaf(3, TAPSCRIPT_LEAF_VERSION, scripts[2]).unwrap();
+        assert_eq!(builder.finalize().unwrap().root(), tree.root());
+
+        // Equal weights give a balanced tree
+        let tree = TapTree::with_huffman_tree((0..8).map(|i| (1, TAPSCRIPT_LEAF_VERSION, vec![i]))).unwrap();
+        assert!(tree.leaves().iter().all(|leaf| leaf.depth() == 3 && leaf.verify(&tree.root())));
+
+        assert_eq!(TapTree::with_huffman_tree(Vec::<(u32, u8, Vec<u8>)>::new()), Err(Error::EmptyTree));
+    }
+
+    #[test]
+    fn builder_errors() {
+        assert_eq!(TapTreeBuilder::new().finalize(), Err(Error::EmptyTree));
+
+        let mut builder = TapTreeBuilder::new();
+        assert_eq!(builder.add_leaf(129, TAPSCRIPT_LEAF_VERSION, &[]), Err(Error::MaxDepthExceeded));
+        assert_eq!(builder.add_leaf(1, 0xc1, &[]), Err(Error::InvalidLeafVersion(0xc1)));
+        assert_eq!(builder.add_leaf(1, 0x50, &[]), Err(Error::InvalidLeafVersion(0x50)));
+
+        // A missing sibling
+        let mut builder = TapTreeBuilder::new();
+        builder.add_leaf(1, TAPSCRIPT_LEAF_VERSION, &[0x51]).unwrap();
+        builder.add_leaf(2, TAPSCRIPT_LEAF_VERSION, &[0x52]).unwrap();
+        assert_eq!(builder.finalize(), Err(Error::IncompleteTree));
+
+        // A leaf after the tree is complete
+        let mut builder = TapTreeBuilder::new();
+        builder.add_leaf(0, TAPSCRIPT_LEAF_VERSION, &[0x51]).unwrap();
+        assert_eq!(builder.add_leaf(0, TAPSCRIPT_LEAF_VERSION, &[0x52]), Err(Error::IncompleteTree));
+        let mut builder = TapTreeBuilder::new();
+        builder.add_leaf(1, TAPSCRIPT_LEAF_VERSION, &[0x51]).unwrap();
+        builder.add_leaf(1, TAPSCRIPT_LEAF_VERSION, &[0x52]).unwrap();
+        builder.add_leaf(1, TAPSCRIPT_LEAF_VERSION, &[0x53]).unwrap();
+        assert_eq!(builder.finalize(), Err(Error::IncompleteTree));
+
+        // The deepest possible leaves
+        let mut builder = TapTreeBuilder::new();
+        for depth in 1..=MAX_DEPTH {
+            builder.add_leaf(depth, TAPSCRIPT_LEAF_VERSION, &[depth as u8]).unwrap();
+        }
+        builder.add_leaf(MAX_DEPTH, TAPSCRIPT_LEAF_VERSION, &[0]).unwrap();
+        let tree = builder.finalize().unwrap();
+        assert!(tree.leaves().iter().all(|leaf| leaf.verify(&tree.root())));
+        let control_block = tree.leaves()[MAX_DEPTH].control_block(&[0; 32], false);
+        assert_eq!(verify_control_block(&tree.root(), &[0], &control_block), Ok(true));
+    }
+}
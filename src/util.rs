@@ -110,8 +110,25 @@ macro_rules! engine_input_impl(
 
 
 /// Creates a new newtype around a [`Hash`] type.
+///
+/// Appending `const` also gives the newtype the `from_inner_const` and `from_hex_const`
+/// constructors used by [`hash!`](crate::hash!), on Rust 1.61 and later. These require the
+/// inner hash type to have an inherent `from_inner_const`, like the hash types of this crate.
+///
+/// ```
+/// use bitcoin_hashes::{hash_newtype, sha256d};
+///
+/// hash_newtype!(BlockHash, sha256d::Hash, 32, doc="A bitcoin block hash.", const);
+/// ```
 #[macro_export]
 macro_rules! hash_newtype {
+    ($newtype:ident, $hash:ty, $len:expr, $docs:meta, const) => {
+        $crate::hash_newtype!($newtype, $hash, $len, $docs, <$hash as $crate::Hash>::DISPLAY_BACKWARD, const);
+    };
+    ($newtype:ident, $hash:ty, $len:expr, $docs:meta, $reverse:expr, const) => {
+        $crate::hash_newtype!($newtype, $hash, $len, $docs, $reverse);
+        $crate::hash_newtype_const_impl!($newtype, $hash, $len, $reverse);
+    };
     ($newtype:ident, $hash:ty, $len:expr, $docs:meta) => {
         $crate::hash_newtype!($newtype, $hash, $len, $docs, <$hash as $crate::Hash>::DISPLAY_BACKWARD);
     };
@@ -211,6 +228,79 @@ macro_rules! hash_newtype {
     };
 }
 
+/// Adds the const constructors of a `const` [`hash_newtype!`] type, on Rust 1.61 and later.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(rust_v_1_61)]
+macro_rules! hash_newtype_const_impl {
+    ($newtype:ident, $hash:ty, $len:expr, $reverse:expr) => {
+        impl $newtype {
+            /// Parses a hash from its hex representation in a const context, e.g. through the
+            /// `hash!` macro.
+            ///
+            /// Like `FromStr`, the string is reversed if the hash is displayed backward.
+            ///
+            /// # Panics
+            ///
+            /// If `s` is not `2 * LEN` hex digits, which fails the build in a const context.
+            pub const fn from_hex_const(s: &str) -> $newtype {
+                let mut inner = [0; $len];
+                let mut i = 0;
+                while i < $len {
+                    inner[i] = $crate::hex::byte_from_hex_const(s, $len, i, $reverse);
+                    i += 1;
+                }
+                $newtype::from_inner_const(inner)
+            }
+
+            /// Constructs a hash from the underlying byte array in a const context.
+            pub const fn from_inner_const(inner: [u8; $len]) -> $newtype {
+                $newtype(<$hash>::from_inner_const(inner))
+            }
+        }
+    };
+}
+
+/// Adds the const constructors of a `const` [`hash_newtype!`] type, on Rust 1.61 and later.
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(rust_v_1_61))]
+macro_rules! hash_newtype_const_impl {
+    ($newtype:ident, $hash:ty, $len:expr, $reverse:expr) => {};
+}
+
+/// Parses a hash from a hex literal at compile time.
+///
+/// Takes the hash type, which may be a [`hash_newtype!`] type declared with `const`, and its hex
+/// representation, reversed if the type is displayed backward like with
+/// [`FromStr`](core::str::FromStr). A hex string of the wrong length or with invalid characters
+/// fails the build. Only available on Rust 1.61 and later.
+///
+/// # Examples
+///
+/// ```
+/// use bitcoin_hashes::{hash, sha256d};
+///
+/// // Hash of the genesis block
+/// const GENESIS: sha256d::Hash = hash!(sha256d::Hash, "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f");
+/// assert_eq!(GENESIS[31], 0);
+/// ```
+///
+/// ```compile_fail
+/// use bitcoin_hashes::{hash, sha256d};
+///
+/// // One character short
+/// let genesis = hash!(sha256d::Hash, "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26");
+/// ```
+#[macro_export]
+#[cfg(rust_v_1_61)]
+macro_rules! hash {
+    ($hash:ty, $hex:expr) => {{
+        const HASH: $hash = <$hash>::from_hex_const($hex);
+        HASH
+    }};
+}
+
 #[cfg(feature = "schemars")]
 #[cfg_attr(docsrs, doc(cfg(feature = "schemars")))]
 pub mod json_hex_string {
@@ -229,9 +319,13 @@ pub mod json_hex_string {
             }
         };
     }
+    define_custom_hex!(len_4, 4);
     define_custom_hex!(len_8, 8);
+    define_custom_hex!(len_16, 16);
     define_custom_hex!(len_20, 20);
+    define_custom_hex!(len_28, 28);
     define_custom_hex!(len_32, 32);
+    define_custom_hex!(len_48, 48);
     define_custom_hex!(len_64, 64);
 }
 
@@ -246,7 +340,7 @@ mod test {
         assert_eq!(hash.to_vec().len(), sha256::Hash::LEN);
     }
 
-    hash_newtype!(TestHash, crate::sha256d::Hash, 32, doc="Test hash.");
+    hash_newtype!(TestHash, crate::sha256d::Hash, 32, doc="Test hash.", const);
 
     #[test]
     fn display() {
@@ -275,4 +369,52 @@ mod test {
         let got = format!("{:#x}", TestHash::all_zeros());
         assert_eq!(got, want)
     }
+
+    // Newtypes without `const` don't need their inner type to have const constructors.
+    hash_newtype!(TestInnerHash, crate::sha256d::Hash, 32, doc="Test hash.");
+    hash_newtype!(TestNestedHash, TestInnerHash, 32, doc="Test hash.");
+
+    #[test]
+    fn nested_newtype() {
+        let hash = TestNestedHash::hash(&[3, 50]);
+        assert_eq!(hash.as_hash().as_hash(), crate::sha256d::Hash::hash(&[3, 50]));
+        assert_eq!(hash.to_string(), hash.as_hash().as_hash().to_string());
+    }
+
+    #[cfg(rust_v_1_61)]
+    hash_newtype!(TestForwardHash, crate::sha256d::Hash, 32, doc="Test hash.", false, const);
+
+    #[test]
+    #[cfg(rust_v_1_61)]
+    fn hash_literal() {
+        use crate::{hash160, sha256d, sha256t};
+
+        const GENESIS: &str = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+        const HASH: sha256d::Hash = hash!(sha256d::Hash, GENESIS);
+        assert_eq!(HASH, GENESIS.parse().unwrap());
+        assert_eq!(HASH[31], 0);
+        assert_eq!(hash!(sha256::Hash, GENESIS), GENESIS.parse().unwrap());
+        assert_eq!(hash!(TestHash, GENESIS), GENESIS.parse().unwrap());
+        assert_eq!(hash!(TestForwardHash, GENESIS), GENESIS.parse().unwrap());
+        assert_eq!(hash!(TestForwardHash, GENESIS)[0], 0);
+
+        const UPPER: &str = "89ABCDEFABBADABBADABBADABBADABBADABBADAB";
+        assert_eq!(hash!(hash160::Hash, UPPER), UPPER.parse().unwrap());
+        const TAGGED: &str = "2e3e95f6bcbbbd9e9bed0a23dec5a3fb8c73a28e8d3fcc6ce8fff4fc2e8042d4";
+        assert_eq!(hash!(sha256t::Hash<sha256t::tags::TapBranchTag>, TAGGED), TAGGED.parse().unwrap());
+    }
+
+    #[test]
+    #[cfg(rust_v_1_61)]
+    #[should_panic(expected = "invalid hex character")]
+    fn hash_literal_invalid_char() {
+        sha256::Hash::from_hex_const("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce2zf");
+    }
+
+    #[test]
+    #[cfg(rust_v_1_61)]
+    #[should_panic(expected = "invalid hex string length")]
+    fn hash_literal_invalid_length() {
+        TestHash::from_hex_const("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce2");
+    }
 }